@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use amethyst::{
+    ecs::prelude::{Component, DenseVecStorage},
+    renderer::SpriteRender,
+};
+use serde::{Deserialize, Serialize};
+
+/// How an animation is described alongside its sprite sheet: the sprites
+/// that make up its frames, in order, by their index in the sheet.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AnimationDef {
+    pub frames: Vec<usize>,
+    /// How long each frame is shown for, at normal speed.
+    pub frame_seconds: f32,
+    #[serde(default)]
+    pub looping: bool,
+}
+
+/// A sequence of sprites, shown one after the other.
+#[derive(Debug)]
+pub struct Animation {
+    pub frames: Vec<SpriteRender>,
+    pub frame_seconds: f32,
+    pub looping: bool,
+}
+
+impl Animation {
+    /// An animation that just shows one sprite.
+    pub fn still(sprite: SpriteRender) -> Animation {
+        Animation {
+            frames: vec![sprite],
+            frame_seconds: 0.0,
+            looping: false,
+        }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.frames.len() as f32 * self.frame_seconds
+    }
+}
+
+/// Resource to look up the sprite sheets' animations by name.
+#[derive(Default)]
+pub struct Animations {
+    pub(crate) animations: HashMap<String, Arc<Animation>>,
+}
+
+impl Animations {
+    pub fn get(&self, name: &str) -> Option<Arc<Animation>> {
+        self.animations.get(name).cloned()
+    }
+}
+
+// ------------------------------------
+
+/// Plays an `Animation` on its entity's `SpriteRender`.
+/// Once an animation that doesn't loop is done, its last frame stays up.
+pub struct SpriteAnimation {
+    animation: Arc<Animation>,
+    elapsed: f32,
+    /// Playback speed, where 1 is normal speed.
+    pub rate: f32,
+    pub looping: bool,
+}
+
+impl Component for SpriteAnimation {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl SpriteAnimation {
+    pub fn new(animation: Arc<Animation>) -> SpriteAnimation {
+        SpriteAnimation {
+            looping: animation.looping,
+            animation,
+            elapsed: 0.0,
+            rate: 1.0,
+        }
+    }
+
+    /// Switch to `animation`, starting from its first frame.
+    pub fn play(&mut self, animation: Arc<Animation>) {
+        *self = SpriteAnimation { rate: self.rate, ..SpriteAnimation::new(animation) };
+    }
+
+    pub fn advance(&mut self, delta_seconds: f32) {
+        self.elapsed += delta_seconds * self.rate;
+        let duration = self.animation.duration();
+        if self.looping && duration > 0.0 {
+            self.elapsed %= duration;
+        }
+    }
+
+    /// The sprite to show right now, if the animation has any frames.
+    pub fn current_frame(&self) -> Option<&SpriteRender> {
+        let frame_count = self.animation.frames.len();
+        if frame_count == 0 || self.animation.frame_seconds <= 0.0 {
+            return self.animation.frames.last();
+        }
+        let frame = (self.elapsed / self.animation.frame_seconds) as usize;
+        self.animation.frames.get(frame.min(frame_count - 1))
+    }
+}
@@ -0,0 +1,161 @@
+use amethyst::ecs::prelude::{Component, HashMapStorage};
+use serde::{Deserialize, Serialize};
+
+use crate::falldown::FollowBounds;
+
+/// Resource describing the play area. The arena spans from (0, 0) to (`width`, `height`)
+/// in world units, and keeps its shape whatever the shape of the window: any space
+/// left over on the sides (or above and below) is covered by bars.
+///
+/// The arena can be resized at any time (e.g. when a level starts); the camera, the bars
+/// and any `ArenaSpan` entities follow along. It's usually loaded from `resources/arena.ron`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Arena {
+    pub width: f32,
+    pub height: f32,
+    /// For arenas that are too tall to show all at once: how much of the arena's height
+    /// is visible. The camera follows its `CameraController`'s target up and down the arena.
+    #[serde(default)]
+    pub visible_height: Option<f32>,
+    /// How far the camera is zoomed in on the arena: 1 shows all of it (or all of `visible_height`),
+    /// more zooms in and less zooms out. The camera eases to a new zoom when this changes.
+    #[serde(default = "default_zoom")]
+    pub zoom: f32,
+    #[serde(default)]
+    pub player_zone: PlayerZone,
+}
+
+fn default_zoom() -> f32 { 1.0 }
+
+impl Default for Arena {
+    fn default() -> Self {
+        Arena {
+            width: 300.0,
+            height: 300.0,
+            visible_height: None,
+            zoom: default_zoom(),
+            player_zone: PlayerZone::default(),
+        }
+    }
+}
+
+/// Where in the arena the player can move.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct PlayerZone {
+    /// How closely the player follows the mouse up and down, like `FollowMouse::y_ratio`.
+    /// 0 keeps it at the same height.
+    pub y_ratio: f32,
+    /// The player stays within a band this tall along the bottom of the arena, as a fraction of its height.
+    pub band: f32,
+}
+
+impl Default for PlayerZone {
+    fn default() -> Self {
+        PlayerZone {
+            y_ratio: 0.0,
+            band: 0.3,
+        }
+    }
+}
+
+impl PlayerZone {
+    /// Bounds that keep the player within the zone, and `margin` units away from the arena's edges.
+    pub fn bounds(&self, margin: f32) -> FollowBounds {
+        FollowBounds {
+            margin,
+            ..FollowBounds::bottom_band(self.band)
+        }
+    }
+}
+
+/// The part of the world a camera shows, in world units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewBounds {
+    pub left: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub top: f32,
+}
+
+impl ViewBounds {
+    /// The smallest view centered on (`center_x`, `center_y`) that shows an area of the given
+    /// `width` and `height` without stretching it, on a screen of the given size in pixels.
+    pub fn fit((center_x, center_y): (f32, f32), (width, height): (f32, f32), screen_width: f32, screen_height: f32) -> ViewBounds {
+        let screen_aspect = screen_width / screen_height;
+        let (view_width, view_height) = if !screen_aspect.is_finite() || screen_aspect <= 0.0 {
+            // e.g. a minimized window
+            (width, height)
+        } else if screen_aspect > width / height {
+            // wider than the area: pillarbox
+            (height * screen_aspect, height)
+        } else {
+            // taller than the area: letterbox
+            (width, width / screen_aspect)
+        };
+
+        ViewBounds {
+            left: center_x - view_width / 2.0,
+            right: center_x + view_width / 2.0,
+            bottom: center_y - view_height / 2.0,
+            top: center_y + view_height / 2.0,
+        }
+    }
+
+    pub fn width(&self) -> f32 { self.right - self.left }
+    pub fn height(&self) -> f32 { self.top - self.bottom }
+
+    pub fn center(&self) -> (f32, f32) {
+        ((self.left + self.right) / 2.0, (self.bottom + self.top) / 2.0)
+    }
+}
+
+impl Arena {
+    /// How much of the arena's height fits on screen at once.
+    pub fn shown_height(&self) -> f32 {
+        self.visible_height.map_or(self.height, |visible| visible.min(self.height))
+    }
+
+    /// The height the camera should be centered on to show as much of the arena around `y` as
+    /// fits, without going past the top or bottom.
+    pub fn follow_height(&self, y: f32) -> f32 {
+        let half = self.shown_height() / 2.0;
+        y.max(half).min(self.height - half)
+    }
+
+    /// The closest position to (`x`, `y`) that's inside the arena.
+    pub fn clamp(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        (x.max(0.0).min(self.width), y.max(0.0).min(self.height))
+    }
+}
+
+// ------------------------------------
+
+/// Which side of the arena a `LetterboxBar` covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarSide {
+    Left,
+    Right,
+    Bottom,
+    Top,
+}
+
+/// One of the bars that cover the view outside of the arena.
+#[derive(Debug, Clone, Copy)]
+pub struct LetterboxBar(pub BarSide);
+
+impl Component for LetterboxBar {
+    type Storage = HashMapStorage<Self>;
+}
+
+/// Stretches its entity across the whole arena, e.g. for a line that spans it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArenaSpan {
+    /// Spans the arena's height, centered vertically. The entity is free to move horizontally.
+    Vertical,
+    /// Spans the arena's width, centered horizontally. The entity is free to move vertically.
+    Horizontal,
+}
+
+impl Component for ArenaSpan {
+    type Storage = HashMapStorage<Self>;
+}
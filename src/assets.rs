@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use amethyst::{
+    assets::{AssetStorage, Loader, ProgressCounter},
+    prelude::*,
+    renderer::{
+        PngFormat, Sprite, SpriteRender, SpriteSheet, SpriteSheetFormat, SpriteSheetHandle, Texture,
+        TextureData, TextureMetadata,
+    },
+};
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::animation::{Animation, AnimationDef, Animations};
+
+/// Target for log messages about loading assets.
+pub const LOG_TARGET: &str = "falldown::assets";
+
+/// The assets the game needs in order to run (usually loaded from `resources/asset_manifest.ron`).
+/// All paths are relative to the assets directory.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AssetManifest {
+    pub sprite_sheets: HashMap<String, SpriteSheetAssets>,
+    /// The names the game uses for its sprites, and where to find each of them.
+    pub sprites: HashMap<String, SpriteRef>,
+    /// The animations defined alongside each sprite sheet, by sheet name.
+    /// They aren't part of the manifest itself, and are only filled in by `load_animations`.
+    #[serde(skip)]
+    pub animations: HashMap<String, HashMap<String, AnimationDef>>,
+    /// Sound effects, by name.
+    #[serde(default)]
+    pub sounds: HashMap<String, String>,
+    /// Background music tracks, played in order.
+    #[serde(default)]
+    pub music: Vec<String>,
+}
+
+impl Default for AssetManifest {
+    fn default() -> Self {
+        let mut sprite_sheets = HashMap::new();
+        sprite_sheets.insert("falldown".to_string(), SpriteSheetAssets {
+            texture: "texture/falldown_spritesheet.png".to_string(),
+            definition: "texture/falldown_spritesheet.ron".to_string(),
+            animations: Some("texture/falldown_animations.ron".to_string()),
+        });
+
+        let sprites = ["block", "player", "pixel"].iter()
+            .enumerate()
+            .map(|(index, name)| (name.to_string(), SpriteRef { sheet: "falldown".to_string(), index }))
+            .collect();
+
+        AssetManifest {
+            sprite_sheets,
+            sprites,
+            animations: HashMap::new(),
+            sounds: HashMap::new(),
+            music: Vec::new(),
+        }
+    }
+}
+
+impl AssetManifest {
+    /// Read the animations for each sprite sheet that has any, from the assets directory.
+    pub fn load_animations(&mut self, assets_directory: &Path) {
+        self.animations = self.sprite_sheets.iter()
+            .filter_map(|(name, assets)| {
+                let path = assets.animations.as_ref()?;
+                Some((name.clone(), SheetAnimations::load(assets_directory.join(path)).animations))
+            })
+            .collect();
+    }
+}
+
+/// A PNG texture, and the RON file describing where each sprite is within it.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpriteSheetAssets {
+    pub texture: String,
+    pub definition: String,
+    /// A RON file of `SheetAnimations` made from this sheet's sprites.
+    #[serde(default)]
+    pub animations: Option<String>,
+}
+
+/// The animations that go with a sprite sheet, by name.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SheetAnimations {
+    pub animations: HashMap<String, AnimationDef>,
+}
+
+/// A sprite within one of the manifest's sprite sheets.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpriteRef {
+    pub sheet: String,
+    pub index: usize,
+}
+
+/// Resource to look up sprites by name, rather than by where they happen to be in a sprite sheet.
+#[derive(Default)]
+pub struct Sprites {
+    sprites: HashMap<String, SpriteRender>,
+}
+
+impl Sprites {
+    pub fn get(&self, name: &str) -> Option<SpriteRender> {
+        self.sprites.get(name).cloned()
+    }
+
+    /// Panics if there's no sprite called `name`. Every sprite the fallback sprite sheet provides
+    /// is always available.
+    pub fn sprite(&self, name: &str) -> SpriteRender {
+        self.get(name).unwrap_or_else(|| panic!("Unknown sprite \"{}\"", name))
+    }
+}
+
+// ------------------------------------
+
+fn load_sprite_sheet(world: &mut World, assets: &SpriteSheetAssets, progress: &mut ProgressCounter) -> SpriteSheetHandle {
+    let texture_handle = {
+        let loader = world.read_resource::<Loader>();
+        let texture_storage = world.read_resource::<AssetStorage<Texture>>();
+        loader.load(
+            assets.texture.as_str(),
+            PngFormat,
+            TextureMetadata::srgb_scale(),
+            &mut *progress,
+            &texture_storage,
+        )
+    };
+
+    let loader = world.read_resource::<Loader>();
+    let sprite_sheet_store = world.read_resource::<AssetStorage<SpriteSheet>>();
+    loader.load(
+        assets.definition.as_str(),
+        SpriteSheetFormat,
+        texture_handle,
+        progress,
+        &sprite_sheet_store,
+    )
+}
+
+/// Start loading every sprite sheet in the manifest, by name.
+pub fn load_sprite_sheets(world: &mut World, manifest: &AssetManifest, progress: &mut ProgressCounter) -> HashMap<String, SpriteSheetHandle> {
+    manifest.sprite_sheets.iter()
+        .map(|(name, assets)| (name.clone(), load_sprite_sheet(world, assets, progress)))
+        .collect()
+}
+
+/// Once loading is done, look up each of the manifest's named sprites in the sprite sheets that loaded.
+/// Sprites that can't be found (because their sheet failed to load, or because they aren't
+/// in it) are taken from the fallback sprite sheet instead.
+pub fn resolve_sprites(world: &mut World, manifest: &AssetManifest, sheets: &HashMap<String, SpriteSheetHandle>) -> Sprites {
+    let mut sprites = Sprites::default();
+    let mut missing = Vec::new();
+    let sprite_counts = sprite_counts(world, sheets);
+    for (name, sprite) in manifest.sprites.iter() {
+        let count = sprite_counts.get(&sprite.sheet).cloned().unwrap_or(0);
+        if sprite.index < count {
+            sprites.sprites.insert(name.clone(), SpriteRender {
+                sprite_sheet: sheets[&sprite.sheet].clone(),
+                sprite_number: sprite.index,
+            });
+        } else {
+            warn!(target: LOG_TARGET, "Sprite \"{}\" (number {} in sheet \"{}\") isn't available", name, sprite.index, sprite.sheet);
+            missing.push(name.clone());
+        }
+    }
+
+    // the game can't run without the fallback sprites, so fill in any the manifest forgot as well
+    for &(name, ..) in FALLBACK_SPRITES.iter() {
+        if !manifest.sprites.contains_key(name) {
+            warn!(target: LOG_TARGET, "The asset manifest doesn't name a \"{}\" sprite", name);
+            missing.push(name.to_string());
+        }
+    }
+
+    if !missing.is_empty() {
+        let fallback_sheet = fallback_sprite_sheet(world);
+        for name in missing {
+            match FALLBACK_SPRITES.iter().position(|&(fallback_name, ..)| fallback_name == name) {
+                Some(index) => {
+                    warn!(target: LOG_TARGET, "Using the built-in fallback for sprite \"{}\"", name);
+                    sprites.sprites.insert(name, SpriteRender {
+                        sprite_sheet: fallback_sheet.clone(),
+                        sprite_number: index,
+                    });
+                },
+                None => error!(target: LOG_TARGET, "There's no fallback for sprite \"{}\"", name),
+            }
+        }
+    }
+
+    sprites
+}
+
+/// Once loading is done, build each sprite sheet's animations out of the sprites that loaded.
+/// Animations whose sheet failed to load, or that use a sprite which isn't in it, are left out,
+/// so whatever would play them just keeps its current sprite.
+pub fn resolve_animations(world: &World, manifest: &AssetManifest, sheets: &HashMap<String, SpriteSheetHandle>) -> Animations {
+    let mut animations = Animations::default();
+    let sprite_counts = sprite_counts(world, sheets);
+    for (sheet, defs) in manifest.animations.iter() {
+        let count = sprite_counts.get(sheet).cloned().unwrap_or(0);
+        for (name, def) in defs.iter() {
+            if let Some(&frame) = def.frames.iter().find(|&&frame| frame >= count) {
+                warn!(target: LOG_TARGET, "Animation \"{}\" uses sprite number {}, which sheet \"{}\" doesn't have", name, frame, sheet);
+                continue;
+            }
+            if animations.animations.contains_key(name) {
+                warn!(target: LOG_TARGET, "Animation \"{}\" is defined by more than one sprite sheet", name);
+            }
+
+            let frames = def.frames.iter()
+                .map(|&sprite_number| SpriteRender { sprite_sheet: sheets[sheet].clone(), sprite_number })
+                .collect();
+            animations.animations.insert(name.clone(), Arc::new(Animation {
+                frames,
+                frame_seconds: def.frame_seconds,
+                looping: def.looping,
+            }));
+        }
+    }
+    animations
+}
+
+/// How many sprites each of the sheets has, counting sheets that (or whose texture) didn't load as empty.
+fn sprite_counts(world: &World, sheets: &HashMap<String, SpriteSheetHandle>) -> HashMap<String, usize> {
+    let sheet_storage = world.read_resource::<AssetStorage<SpriteSheet>>();
+    let texture_storage = world.read_resource::<AssetStorage<Texture>>();
+    sheets.iter()
+        .map(|(name, handle)| {
+            let count = sheet_storage.get(handle)
+                .filter(|sheet| texture_storage.get(&sheet.texture).is_some())
+                .map_or(0, |sheet| sheet.sprites.len());
+            (name.clone(), count)
+        })
+        .collect()
+}
+
+/// Log every asset that failed to load, along with why.
+pub fn report_errors(progress: &ProgressCounter) {
+    let errors = progress.errors();
+    if errors.is_empty() {
+        warn!(target: LOG_TARGET, "Loading failed, but no asset reported an error");
+    }
+    for failure in errors {
+        error!(target: LOG_TARGET, "Failed to load asset \"{}\": {}", failure.asset_name, failure.error);
+        for cause in failure.error.iter().skip(1) {
+            error!(target: LOG_TARGET, "    caused by: {}", cause);
+        }
+    }
+}
+
+// ------------------------------------
+
+/// Width and height of the fallback sprite sheet's texture.
+const FALLBACK_WIDTH: u32 = 24;
+const FALLBACK_HEIGHT: u32 = 20;
+
+/// The name of each fallback sprite, and where it is in the texture as (left, top, width, height).
+const FALLBACK_SPRITES: [(&str, u32, u32, u32, u32); 3] = [
+    ("block", 0, 0, 10, 10),
+    ("player", 11, 0, 13, 19),
+    ("pixel", 0, 11, 1, 1),
+];
+
+/// Build a plain white stand-in for the game's sprite sheet, for when it couldn't be loaded.
+/// The sprites are tinted when they're drawn, so the game is still playable with them.
+fn fallback_sprite_sheet(world: &mut World) -> SpriteSheetHandle {
+    let pixels = fallback_pixels();
+    let metadata = TextureMetadata::srgb_scale().with_size(FALLBACK_WIDTH as u16, FALLBACK_HEIGHT as u16);
+
+    let loader = world.read_resource::<Loader>();
+    let texture = loader.load_from_data(
+        TextureData::U8(pixels, metadata),
+        (),
+        &world.read_resource::<AssetStorage<Texture>>(),
+    );
+
+    let sprites = FALLBACK_SPRITES.iter()
+        .map(|&(_, left, top, width, height)| {
+            Sprite::from_pixel_values(FALLBACK_WIDTH, FALLBACK_HEIGHT, width, height, left, top, [0.0, 0.0])
+        })
+        .collect();
+
+    loader.load_from_data(
+        SpriteSheet { texture, sprites },
+        (),
+        &world.read_resource::<AssetStorage<SpriteSheet>>(),
+    )
+}
+
+/// RGBA pixels for the fallback sprite sheet: a round block, a player with
+/// rounded-off corners, and a single pixel.
+fn fallback_pixels() -> Vec<u8> {
+    let mut pixels = vec![0u8; (FALLBACK_WIDTH * FALLBACK_HEIGHT * 4) as usize];
+    let mut fill = |x: u32, y: u32| {
+        let i = ((y * FALLBACK_WIDTH + x) * 4) as usize;
+        pixels[i..i + 4].copy_from_slice(&[255, 255, 255, 255]);
+    };
+
+    for &(name, left, top, width, height) in FALLBACK_SPRITES.iter() {
+        let (center_x, center_y) = (width as f32 / 2.0, height as f32 / 2.0);
+        for y in 0..height {
+            for x in 0..width {
+                // distance from the sprite's center, relative to its size, measured from the pixel's center
+                let dx = (x as f32 + 0.5 - center_x) / center_x;
+                let dy = (y as f32 + 0.5 - center_y) / center_y;
+                let inside = match name {
+                    "block" => dx * dx + dy * dy <= 1.0,
+                    "player" => dx.abs() < 0.8 || dy.abs() < 0.8 || (dx.abs() - 0.8).hypot(dy.abs() - 0.8) <= 0.2,
+                    _ => true,
+                };
+                if inside {
+                    fill(left + x, top + y);
+                }
+            }
+        }
+    }
+
+    pixels
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use amethyst::{
+    assets::{AssetStorage, Loader, ProgressCounter},
+    audio::{output::Output, AudioSink, FlacFormat, Mp3Format, OggFormat, Source, SourceHandle, WavFormat},
+    prelude::*,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::assets::{AssetManifest, LOG_TARGET};
+
+/// Audio settings (usually loaded from `resources/audio.ron`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AudioConfig {
+    /// With audio disabled, no audio assets are loaded and nothing is played,
+    /// as if there was no audio device.
+    pub enabled: bool,
+    /// From 0 (silent) to 1 (full volume).
+    pub effects_volume: f32,
+    pub music_volume: f32,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        AudioConfig {
+            enabled: true,
+            effects_volume: 0.5,
+            music_volume: 0.25,
+        }
+    }
+}
+
+// ------------------------------------
+
+/// Resource to look up sound effects by name.
+#[derive(Default)]
+pub struct Sounds {
+    sounds: HashMap<String, SourceHandle>,
+}
+
+impl Sounds {
+    /// Play the sound effect called `name` once, if there's an audio device to play it on
+    /// and it loaded. Otherwise, nothing happens.
+    pub fn play(&self, name: &str, volume: f32, storage: &AssetStorage<Source>, output: Option<&Output>) {
+        let output = match output {
+            Some(output) => output,
+            None => return,
+        };
+        if let Some(source) = self.sounds.get(name).and_then(|handle| storage.get(handle)) {
+            output.play_once(source, volume);
+        }
+    }
+}
+
+/// Resource holding the background music tracks, which are played one after the other, forever.
+#[derive(Default)]
+pub struct Music {
+    tracks: Vec<SourceHandle>,
+    next: usize,
+}
+
+impl Music {
+    /// The track to play once the current one is over.
+    pub fn next_track(&mut self) -> Option<SourceHandle> {
+        if self.tracks.is_empty() {
+            return None;
+        }
+        let track = self.tracks[self.next % self.tracks.len()].clone();
+        self.next = (self.next + 1) % self.tracks.len();
+        Some(track)
+    }
+}
+
+// ------------------------------------
+
+/// Start loading every sound effect and music track in the manifest.
+pub fn load_audio(world: &mut World, manifest: &AssetManifest, progress: &mut ProgressCounter) -> (Sounds, Music) {
+    let sounds = manifest.sounds.iter()
+        .filter_map(|(name, path)| Some((name.clone(), load_source(world, path, progress)?)))
+        .collect();
+    let tracks = manifest.music.iter()
+        .filter_map(|path| load_source(world, path, progress))
+        .collect();
+
+    (Sounds { sounds }, Music { tracks, next: 0 })
+}
+
+/// Load an audio file, picking the format from its extension.
+fn load_source(world: &mut World, path: &str, progress: &mut ProgressCounter) -> Option<SourceHandle> {
+    let loader = world.read_resource::<Loader>();
+    let storage = world.read_resource::<AssetStorage<Source>>();
+
+    let extension = Path::new(path).extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+    let handle = match extension.as_ref().map(String::as_str) {
+        Some("ogg") => loader.load(path, OggFormat, (), &mut *progress, &storage),
+        Some("wav") => loader.load(path, WavFormat, (), &mut *progress, &storage),
+        Some("flac") => loader.load(path, FlacFormat, (), &mut *progress, &storage),
+        Some("mp3") => loader.load(path, Mp3Format, (), &mut *progress, &storage),
+        _ => {
+            warn!(target: LOG_TARGET, "Don't know how to load audio file \"{}\", skipping it", path);
+            return None;
+        },
+    };
+    Some(handle)
+}
+
+/// Make the loaded audio available to the game, and set the music volume.
+pub fn start_audio(world: &mut World, sounds: Sounds, music: Music) {
+    let music_volume = world.read_resource::<AudioConfig>().music_volume;
+    // there's no sink without an audio device
+    if let Some(mut sink) = world.res.try_fetch_mut::<AudioSink>() {
+        sink.set_volume(music_volume);
+    }
+    world.add_resource(sounds);
+    world.add_resource(music);
+}
@@ -0,0 +1,86 @@
+use amethyst::ecs::prelude::{Component, Entity, HashMapStorage};
+use rand::Rng;
+
+use crate::util::ease_towards;
+
+/// Controls how the camera moves around the arena: shaking, zooming and following an entity.
+///
+/// Shaking is driven by "trauma", from 0 to 1, which is added by things like hits and
+/// wears off over time. The shake grows with the square of the trauma, so small knocks
+/// are subtle and big ones are violent.
+pub struct CameraController {
+    trauma: f32,
+    /// How much trauma wears off per second.
+    pub trauma_decay: f32,
+    /// How much trauma the player running into a block of the wrong color causes.
+    pub hit_trauma: f32,
+    /// How far (in world units) and how much (in radians) the camera can be moved by shaking.
+    pub max_shake_offset: f32,
+    pub max_shake_angle: f32,
+
+    zoom: f32,
+    pub zoom_half_life: f32,
+
+    /// The entity to follow up and down arenas that are too tall to show all at once.
+    pub follow: Option<Entity>,
+    pub follow_half_life: f32,
+    follow_height: Option<f32>,
+}
+
+impl Component for CameraController {
+    type Storage = HashMapStorage<Self>;
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        CameraController {
+            trauma: 0.0,
+            trauma_decay: 1.5,
+            hit_trauma: 0.5,
+            max_shake_offset: 6.0,
+            max_shake_angle: 0.04,
+            zoom: 1.0,
+            zoom_half_life: 0.25,
+            follow: None,
+            follow_half_life: 0.15,
+            follow_height: None,
+        }
+    }
+}
+
+impl CameraController {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    pub fn zoom(&self) -> f32 { self.zoom }
+
+    /// The height the camera is centered on, if it's following something.
+    pub fn follow_height(&self) -> Option<f32> { self.follow_height }
+
+    /// Wear off the trauma and ease the zoom and follow height towards their targets.
+    /// `target_height` is where the camera should be centered to follow its target,
+    /// or `None` if it isn't following anything.
+    pub fn update(&mut self, delta_seconds: f32, target_zoom: f32, target_height: Option<f32>) {
+        self.trauma = (self.trauma - self.trauma_decay * delta_seconds).max(0.0);
+        self.zoom = ease_towards(self.zoom, target_zoom, self.zoom_half_life, delta_seconds);
+        self.follow_height = target_height.map(|target| match self.follow_height {
+            Some(current) => ease_towards(current, target, self.follow_half_life, delta_seconds),
+            // start right on a new target
+            None => target,
+        });
+    }
+
+    /// A random (x, y) offset and angle to shake the camera by this frame.
+    pub fn shake<R: Rng>(&self, rng: &mut R) -> ((f32, f32), f32) {
+        let shake = self.trauma * self.trauma;
+        if shake <= 0.0 {
+            return ((0.0, 0.0), 0.0);
+        }
+        let offset = (
+            self.max_shake_offset * shake * rng.gen_range(-1.0, 1.0),
+            self.max_shake_offset * shake * rng.gen_range(-1.0, 1.0),
+        );
+        (offset, self.max_shake_angle * shake * rng.gen_range(-1.0, 1.0))
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use ncollide3d::world::CollisionGroups;
+use serde::{Deserialize, Serialize};
+
+/// The most layers ncollide's `CollisionGroups` can represent.
+pub const MAX_LAYERS: usize = 30;
+
+/// Config (usually loaded from `resources/collision_layers.ron`) naming the collision
+/// layers, and which pairs of layers are allowed to collide with each other.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CollisionLayerConfig {
+    pub layers: Vec<String>,
+    pub interactions: Vec<(String, String)>,
+}
+
+impl Default for CollisionLayerConfig {
+    fn default() -> Self {
+        CollisionLayerConfig {
+            layers: vec!["player".to_string(), "enemy".to_string()],
+            interactions: vec![("player".to_string(), "enemy".to_string())],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CollisionLayerError {
+    TooManyLayers(usize),
+    DuplicateLayer(String),
+    UnknownLayer(String),
+}
+
+impl fmt::Display for CollisionLayerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CollisionLayerError::TooManyLayers(count) => write!(f, "{} collision layers were defined, but at most {} are supported", count, MAX_LAYERS),
+            CollisionLayerError::DuplicateLayer(name) => write!(f, "collision layer \"{}\" was defined more than once", name),
+            CollisionLayerError::UnknownLayer(name) => write!(f, "unknown collision layer \"{}\"", name),
+        }
+    }
+}
+
+impl std::error::Error for CollisionLayerError {}
+
+// ------------------------------------
+
+/// Resource mapping collision layer names to ncollide collision group ids,
+/// along with a symmetric matrix of which layers interact with which.
+/// Use `groups` to build the `CollisionGroups` for a `Collider`.
+#[derive(Debug, Clone)]
+pub struct CollisionLayers {
+    names: Vec<String>,
+    /// bit `j` of `matrix[i]` is set if layer `i` interacts with layer `j`
+    matrix: Vec<u32>,
+}
+
+impl CollisionLayers {
+    pub fn from_config(config: &CollisionLayerConfig) -> Result<CollisionLayers, CollisionLayerError> {
+        if config.layers.len() > MAX_LAYERS {
+            return Err(CollisionLayerError::TooManyLayers(config.layers.len()));
+        }
+
+        let mut layers = CollisionLayers {
+            names: Vec::with_capacity(config.layers.len()),
+            matrix: vec![0; config.layers.len()],
+        };
+        for name in config.layers.iter() {
+            if layers.names.contains(name) {
+                return Err(CollisionLayerError::DuplicateLayer(name.clone()));
+            }
+            layers.names.push(name.clone());
+        }
+        for (a, b) in config.interactions.iter() {
+            layers.set_interaction(a, b, true)?;
+        }
+        Ok(layers)
+    }
+
+    /// The collision group id of the layer with the given `name`.
+    pub fn layer(&self, name: &str) -> Result<usize, CollisionLayerError> {
+        self.names.iter()
+            .position(|n| n == name)
+            .ok_or_else(|| CollisionLayerError::UnknownLayer(name.to_string()))
+    }
+
+    /// Allow (or disallow) the two layers to collide with each other.
+    pub fn set_interaction(&mut self, a: &str, b: &str, interacts: bool) -> Result<(), CollisionLayerError> {
+        let a = self.layer(a)?;
+        let b = self.layer(b)?;
+        if interacts {
+            self.matrix[a] |= 1 << b;
+            self.matrix[b] |= 1 << a;
+        } else {
+            self.matrix[a] &= !(1 << b);
+            self.matrix[b] &= !(1 << a);
+        }
+        Ok(())
+    }
+
+    /// Collision groups for a collider that is a member of the named layer,
+    /// and collides with every layer that layer interacts with.
+    ///
+    /// Panics if there is no layer with the given `name`; use `try_groups`
+    /// if the name didn't come from the code itself.
+    pub fn groups(&self, name: &str) -> CollisionGroups {
+        self.try_groups(&[name]).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Collision groups for a collider that is a member of all of the named layers,
+    /// and collides with every layer that any of them interact with.
+    pub fn try_groups(&self, names: &[&str]) -> Result<CollisionGroups, CollisionLayerError> {
+        let mut membership = Vec::with_capacity(names.len());
+        let mut whitelist_mask = 0u32;
+        for name in names {
+            let layer = self.layer(name)?;
+            membership.push(layer);
+            whitelist_mask |= self.matrix[layer];
+        }
+        let whitelist: Vec<usize> = (0..self.names.len())
+            .filter(|layer| whitelist_mask & (1 << layer) != 0)
+            .collect();
+
+        Ok(CollisionGroups::new()
+            .with_membership(&membership)
+            .with_whitelist(&whitelist))
+    }
+}
+
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        CollisionLayers::from_config(&CollisionLayerConfig::default())
+            .expect("Default collision layer config is invalid")
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use amethyst::{
+    ecs::prelude::*,
+    ecs::world::Index,
+    shrev::Event,
+};
+
+use crate::storage::{DetailedComponentEvent, DetailedReader, MarkModified, RemovalTracked, ToEvent};
+
+/// A component that is mirrored into some structure outside of the ECS,
+/// e.g. a collision world, an audio mixer or a particle system.
+///
+/// The component's storage should be a `RemovalFlaggedStorage` whose removal events
+/// carry the component's handle (via `ToEvent`), so that the mirrored object can still
+/// be found after the component itself is gone.
+/// A `ComponentSync` (or the generic `SyncExternalWorld` system) takes care of
+/// calling `insert_into`, `update_in` and `remove_from` at the right times.
+/// Changes to a component are only copied to the external world after they are reported
+/// to the storage with `mark_modified` or `modify`.
+pub trait ExternalSync: Component {
+    /// The external structure. It is expected to be available as a resource.
+    type World: Resource;
+
+    /// Identifies the mirrored object within the external `World`.
+    type Handle: Event + Clone;
+
+    fn handle(&self) -> Option<Self::Handle>;
+
+    fn set_handle(&mut self, handle: Self::Handle);
+
+    /// Add a mirror of this component (which belongs to `entity`) to the external world.
+    fn insert_into(&self, entity: Entity, world: &mut Self::World) -> Self::Handle;
+
+    /// Copy this component's current state onto its existing mirror in the external world.
+    fn update_in(&self, handle: &Self::Handle, world: &mut Self::World);
+
+    /// Remove a mirror from the external world, after its component was removed.
+    fn remove_from(handles: &[Self::Handle], world: &mut Self::World);
+
+    /// Remove the mirrors of a whole storage's worth of components at once,
+    /// after the storage was cleared.
+    fn clear(handles: &[Self::Handle], world: &mut Self::World) {
+        Self::remove_from(handles, world);
+    }
+
+    /// Optional counter that changes whenever the mirrored parts of the component change.
+    /// When present, modified components whose revision is the same as the last time they
+    /// were copied into the external world are skipped.
+    fn revision(&self) -> Option<u32> { None }
+}
+
+// -----------------
+
+/// The bookkeeping needed to keep an `ExternalSync` component's storage and its external world
+/// in sync. The `SyncExternalWorld` system owns one of these and calls `sync` every frame;
+/// systems that need to do more than just mirror the components can do the same.
+pub struct ComponentSync<C>
+where
+    C: ExternalSync + ToEvent<Option<<C as ExternalSync>::Handle>>,
+    C::Storage: RemovalTracked<Option<<C as ExternalSync>::Handle>> + MarkModified,
+{
+    reader: Option<DetailedReader<Option<C::Handle>>>,
+    inserted: BitSet,
+    modified: BitSet,
+    removed: Vec<C::Handle>,
+    cleared: Vec<C::Handle>,
+    /// The handle of every mirrored component, so the mirror of a component
+    /// that was replaced can still be found after it's gone.
+    handles: HashMap<Index, C::Handle>,
+    applied_revisions: HashMap<Index, u32>,
+}
+
+impl<C> Default for ComponentSync<C>
+where
+    C: ExternalSync + ToEvent<Option<<C as ExternalSync>::Handle>>,
+    C::Storage: RemovalTracked<Option<<C as ExternalSync>::Handle>> + MarkModified,
+{
+    fn default() -> Self {
+        ComponentSync {
+            reader: None,
+            inserted: BitSet::new(),
+            modified: BitSet::new(),
+            removed: Vec::new(),
+            cleared: Vec::new(),
+            handles: HashMap::new(),
+            applied_revisions: HashMap::new(),
+        }
+    }
+}
+
+impl<C> ComponentSync<C>
+where
+    C: ExternalSync + ToEvent<Option<<C as ExternalSync>::Handle>>,
+    C::Storage: RemovalTracked<Option<<C as ExternalSync>::Handle>> + MarkModified,
+{
+    /// Start listening to the component storage's events. Call this from the owning system's `setup`.
+    pub fn setup(&mut self, res: &mut Resources) {
+        let mut storage: WriteStorage<C> = SystemData::fetch(res);
+        self.reader = Some(DetailedReader::new(&mut storage));
+    }
+
+    /// Apply every insertion, modification and removal since the last call to the external world.
+    pub fn sync(&mut self, entities: &Entities, storage: &mut WriteStorage<C>, world: &mut C::World) {
+        self.inserted.clear();
+        self.modified.clear();
+        self.removed.clear();
+        self.cleared.clear();
+
+        let reader = self.reader.as_mut().expect("ComponentSync::setup was never called");
+        for event in reader.read_alive(storage, entities) {
+            match event {
+                DetailedComponentEvent::Inserted(entity) => {
+                    self.inserted.add(entity.id());
+                },
+                DetailedComponentEvent::Modified(entity) => {
+                    self.modified.add(entity.id());
+                },
+                DetailedComponentEvent::Removed(entity, handle) => {
+                    self.inserted.remove(entity.id());
+                    self.modified.remove(entity.id());
+                    self.handles.remove(&entity.id());
+                    self.applied_revisions.remove(&entity.id());
+                    // components removed before they were ever synced don't have a handle yet
+                    if let Some(handle) = handle {
+                        self.removed.push(handle.clone());
+                    }
+                },
+                DetailedComponentEvent::Cleared(components) => {
+                    // anything inserted before the clear is gone as well
+                    self.inserted.clear();
+                    self.modified.clear();
+                    self.handles.clear();
+                    self.applied_revisions.clear();
+                    self.cleared.extend(self.removed.drain(..));
+                    self.cleared.extend(components.iter().filter_map(|(_, handle)| handle.clone()));
+                },
+            }
+        }
+
+        // `Storage::insert` swaps a replacement into an occupied slot without the storage hearing
+        // about it, so replaced components are found by their missing handle instead.
+        // Their predecessor's mirror is removed, and they're inserted like new ones.
+        let replaced: Vec<_> = (&**entities, &*storage, !&self.inserted).join()
+            .filter(|(entity, component, _)| component.handle().is_none() && entities.is_alive(*entity))
+            .map(|(entity, _, _)| entity.id())
+            .collect();
+        for id in replaced {
+            self.inserted.add(id);
+            self.modified.remove(id);
+            self.applied_revisions.remove(&id);
+            if let Some(handle) = self.handles.remove(&id) {
+                self.removed.push(handle);
+            }
+        }
+
+        // remove first, in case an inserted component reuses a removed one's index
+        if !self.cleared.is_empty() {
+            C::clear(&self.cleared, world);
+        }
+        if !self.removed.is_empty() {
+            C::remove_from(&self.removed, world);
+        }
+
+        for (entity, component, _) in (&**entities, &mut *storage, &self.inserted).join() {
+            let component: &mut C = component;
+            let handle = component.insert_into(entity, world);
+            self.handles.insert(entity.id(), handle.clone());
+            component.set_handle(handle);
+            if let Some(revision) = component.revision() {
+                self.applied_revisions.insert(entity.id(), revision);
+            }
+        }
+
+        // newly-inserted components already have their latest state
+        for (entity, component, _, _) in (&**entities, &*storage, &self.modified, !&self.inserted).join() {
+            let component: &C = component;
+            let handle = match component.handle() {
+                Some(handle) => handle,
+                None => continue,
+            };
+            if let Some(revision) = component.revision() {
+                if self.applied_revisions.insert(entity.id(), revision) == Some(revision) {
+                    continue;
+                }
+            }
+            component.update_in(&handle, world);
+        }
+    }
+}
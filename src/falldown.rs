@@ -1,26 +1,27 @@
 use std::{
-    collections::VecDeque,
+    collections::{HashMap, VecDeque},
     convert::From,
+    sync::Arc,
 };
 
 use amethyst::{
-    assets::{AssetStorage, Completion, Loader, Progress, ProgressCounter},
+    assets::{Completion, ProgressCounter},
     core::{
-        nalgebra::Vector3,
+        nalgebra::{Isometry3, Point3, Vector3},
+        Time,
         transform::{
             components::Parent,
             Transform,
         },
     },
-    ecs::prelude::{Component, DenseVecStorage, Entity, HashMapStorage, VecStorage},
+    ecs::prelude::{Component, DenseVecStorage, Entity, HashMapStorage, Join, NullStorage, RunNow, VecStorage},
     prelude::*,
     renderer::{
-        Camera, PngFormat, Projection, Rgba, SpriteRender, SpriteSheet, SpriteSheetFormat,
-        SpriteSheetHandle, Texture, TextureMetadata, Transparent,
+        Camera, DebugLines, Projection, Rgba, SpriteRender, SpriteSheetHandle, Transparent,
     },
 };
+use log::{info, trace};
 use ncollide3d::{
-    events::ContactEvent,
     shape::{Ball, ShapeHandle},
     world::{CollisionGroups, CollisionObjectHandle, CollisionWorld, GeometricQueryType},
 };
@@ -29,26 +30,28 @@ use rand::{
     Rng,
     thread_rng
 };
-
-use crate::storage::{RemovalFlaggedStorage, ToEvent};
+use serde::Serialize;
+
+use crate::animation::{Animation, SpriteAnimation};
+use crate::arena::{Arena, ArenaSpan, BarSide, LetterboxBar, PlayerZone};
+use crate::assets::{self, AssetManifest, Sprites};
+use crate::audio::{self, AudioConfig, Music, Sounds};
+use crate::camera::CameraController;
+use crate::collision_layers::CollisionLayers;
+use crate::external_sync::ExternalSync;
+use crate::gameplay_log::{GameplayEvent, GameplayLog};
+use crate::loading_screen::LoadingScreen;
+use crate::storage::{DetectChanges, RemovalFlaggedStorage, ToEvent};
+use crate::systems::SyncExternalWorld;
+use crate::util::ease_towards;
+
+#[cfg(test)]
+mod tests;
 
 // ------------------------------------
 
-pub const ARENA_HEIGHT: f32 = 300.;
-pub const ARENA_WIDTH: f32 = 300.;
-
-// ------------------------------------
-
-pub fn player_collision_group() -> CollisionGroups {
-    CollisionGroups::new()
-        .with_membership(&[0])
-        .with_whitelist(&[1])
-}
-pub fn enemy_collision_group() -> CollisionGroups {
-    CollisionGroups::new()
-        .with_membership(&[1])
-        .with_whitelist(&[0])
-}
+/// Target for log messages about the game's states.
+const STATE_LOG_TARGET: &str = "falldown::state";
 
 // ------------------------------------
 
@@ -63,28 +66,147 @@ impl Component for FallingObject {
     type Storage = VecStorage<Self>;
 }
 
+/// Marks a falling block that the player already ran into. It keeps falling,
+/// but it doesn't count as missed when it leaves the bottom of the arena.
+#[derive(Default, Debug)]
+pub struct Hit;
+
+impl Component for Hit {
+    type Storage = NullStorage<Self>;
+}
+
 // ------------------------------------
 
+/// Moves its entity towards the mouse, easing in so it takes the same time at any frame rate.
+#[derive(Debug, Clone, Copy)]
 pub struct FollowMouse {
+    /// How closely each axis follows the mouse: 1 eases in over `half_life`,
+    /// 0.5 takes twice as long, and 0 doesn't follow the mouse on that axis at all.
     pub x_ratio: f32,
     pub y_ratio: f32,
+    /// Seconds it takes to halve the distance to the mouse. 0 sticks right to it.
+    pub half_life: f32,
+    /// The fastest the entity will move, in units per second.
+    pub max_speed: Option<f32>,
+    /// The entity stays put while the mouse is within this distance of it.
+    pub dead_zone: f32,
+    /// Where in the arena the entity is allowed to go.
+    pub bounds: FollowBounds,
+    /// Follow the arena's `player_zone` (which can change from level to level) instead of
+    /// `y_ratio` and the bounds, keeping just the bounds' margin.
+    pub in_player_zone: bool,
 }
 
 impl Component for FollowMouse {
     type Storage = DenseVecStorage<Self>;
 }
 
+impl Default for FollowMouse {
+    fn default() -> Self {
+        FollowMouse {
+            x_ratio: 1.0,
+            y_ratio: 1.0,
+            half_life: 0.0,
+            max_speed: None,
+            dead_zone: 0.0,
+            bounds: FollowBounds::default(),
+            in_player_zone: false,
+        }
+    }
+}
+
+impl FollowMouse {
+    /// This follower, moving within `zone` instead of its own `y_ratio` and bounds.
+    pub fn in_zone(&self, zone: &PlayerZone) -> FollowMouse {
+        FollowMouse {
+            y_ratio: zone.y_ratio,
+            bounds: zone.bounds(self.bounds.margin),
+            ..*self
+        }
+    }
+
+    /// Where to move to from `(x, y)` after `delta_seconds` of following the mouse at `(target_x, target_y)`.
+    pub fn step(&self, (x, y): (f32, f32), (target_x, target_y): (f32, f32), delta_seconds: f32) -> (f32, f32) {
+        // only the axes being followed count towards the dead zone
+        let offset_x = if self.x_ratio > 0.0 { target_x - x } else { 0.0 };
+        let offset_y = if self.y_ratio > 0.0 { target_y - y } else { 0.0 };
+        if offset_x.hypot(offset_y) <= self.dead_zone {
+            return (x, y);
+        }
+
+        let mut step_x = self.ease_axis(x, target_x, self.x_ratio, delta_seconds) - x;
+        let mut step_y = self.ease_axis(y, target_y, self.y_ratio, delta_seconds) - y;
+        if let Some(max_speed) = self.max_speed {
+            let max_step = max_speed * delta_seconds;
+            let step = step_x.hypot(step_y);
+            if step > max_step {
+                step_x *= max_step / step;
+                step_y *= max_step / step;
+            }
+        }
+        (x + step_x, y + step_y)
+    }
+
+    fn ease_axis(&self, current: f32, target: f32, ratio: f32, delta_seconds: f32) -> f32 {
+        if ratio <= 0.0 {
+            current
+        } else {
+            ease_towards(current, target, self.half_life / ratio, delta_seconds)
+        }
+    }
+}
+
+/// Limits on where a `FollowMouse` entity can go. It's always kept inside the arena,
+/// `margin` world units away from its edges, and further limited by any of the `min`s and `max`es
+/// that are set. Those are fractions of the arena's width or height, so they keep up when it's resized.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FollowBounds {
+    pub margin: f32,
+    pub min_x: Option<f32>,
+    pub max_x: Option<f32>,
+    pub min_y: Option<f32>,
+    pub max_y: Option<f32>,
+}
+
+impl FollowBounds {
+    /// Keep to a band along the bottom of the arena, `height` times as tall as the arena.
+    pub fn bottom_band(height: f32) -> FollowBounds {
+        FollowBounds {
+            max_y: Some(height),
+            ..Default::default()
+        }
+    }
+
+    /// The closest position to (`x`, `y`) that's within these bounds in the given arena.
+    /// If the bounds don't leave any room, the arena's edges win.
+    pub fn clamp(&self, (x, y): (f32, f32), arena: &Arena) -> (f32, f32) {
+        let x = clamp_axis(x, self.min_x, self.max_x, self.margin, arena.width);
+        let y = clamp_axis(y, self.min_y, self.max_y, self.margin, arena.height);
+        (x, y)
+    }
+}
+
+fn clamp_axis(value: f32, min: Option<f32>, max: Option<f32>, margin: f32, arena_size: f32) -> f32 {
+    let value = min.map_or(value, |min| value.max(min * arena_size));
+    let value = max.map_or(value, |max| value.min(max * arena_size));
+    // an arena smaller than the margins only has room in the middle
+    let margin = margin.min(arena_size / 2.0);
+    value.max(margin).min(arena_size - margin)
+}
+
 // ------------------------------------
 
 //#[derive(Default)]
 pub struct Player {
     pub trail: MovementTrail,
+    pub tilt: Tilt,
 }
 
 impl Player {
     fn new() -> Player {
         Player {
-            trail: MovementTrail::new(3),
+            trail: MovementTrail::new(0.05),
+            tilt: Tilt::default(),
         }
     }
 }
@@ -95,7 +217,7 @@ impl Component for Player {
 
 // ------------------------------------
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize)]
 pub enum ColorType {
     Green,
     Blue,
@@ -180,30 +302,92 @@ impl Component for Affiliation {
 
 // ------------------------------------
 
+/// A short history of timestamped positions, used to estimate
+/// how fast something has been moving over a window of time.
 pub struct MovementTrail {
-    capacity: usize,
-    trail: VecDeque<Vector3<f32>>,
+    window: f64,
+    trail: VecDeque<(f64, Vector3<f32>)>,
 }
 
 impl MovementTrail {
-    pub fn new(capacity: usize) -> MovementTrail {
+    pub fn new(window_seconds: f64) -> MovementTrail {
         MovementTrail {
-            capacity,
-            trail: VecDeque::with_capacity(capacity),
+            window: window_seconds,
+            trail: VecDeque::new(),
         }
     }
 
-    pub fn push(&mut self, pos: Vector3<f32>) {
-        if self.trail.len() >= self.capacity {
+    /// Record the position at the given `time` (in seconds), forgetting any
+    /// positions that have fallen out of the window. The newest position that
+    /// is older than the window is kept, so the trail always spans the full window.
+    pub fn push(&mut self, time: f64, pos: Vector3<f32>) {
+        self.trail.push_back((time, pos));
+        while self.trail.len() > 2 && time - self.trail[1].0 >= self.window {
             self.trail.pop_front();
         }
-        self.trail.push_back(pos);
     }
 
-    pub fn oldest(&self) -> Option<&Vector3<f32>> {
-        self.trail.front()
+    /// The average velocity (in units per second) over the trail's window.
+    pub fn velocity(&self) -> Vector3<f32> {
+        match (self.trail.front(), self.trail.back()) {
+            (Some((t0, p0)), Some((t1, p1))) if t1 > t0 => (p1 - p0) / ((t1 - t0) as f32),
+            _ => Vector3::zeros(),
+        }
+    }
+}
+
+// ------------------------------------
+
+/// Settings and state for how the player leans into its horizontal movement.
+/// The lean angle chases a target angle (proportional to the player's speed)
+/// using a damped spring, so it doesn't snap around when the mouse jitters.
+pub struct Tilt {
+    /// The largest angle (in degrees) the player will lean in either direction.
+    pub max_angle: f32,
+    /// How many degrees to lean for every unit-per-second of horizontal speed.
+    pub degrees_per_speed: f32,
+    /// Speeds below this (in units per second) don't cause any lean.
+    pub dead_zone: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+    angle: f32,
+    angular_velocity: f32,
+}
+
+impl Default for Tilt {
+    fn default() -> Self {
+        Tilt {
+            max_angle: 45.0,
+            degrees_per_speed: 0.1,
+            dead_zone: 2.0,
+            // critically damped: damping = 2 * sqrt(stiffness)
+            stiffness: 900.0,
+            damping: 60.0,
+            angle: 0.0,
+            angular_velocity: 0.0,
+        }
     }
+}
+
+impl Tilt {
+    /// The current lean angle, in degrees.
+    pub fn angle(&self) -> f32 { self.angle }
 
+    /// The angle the spring is pulling towards when moving at the given horizontal speed.
+    pub fn target_angle(&self, x_speed: f32) -> f32 {
+        if x_speed.abs() > self.dead_zone {
+            (x_speed * -self.degrees_per_speed).min(self.max_angle).max(-self.max_angle)
+        } else {
+            0.0
+        }
+    }
+
+    /// Advance the spring towards `target` by `dt` seconds.
+    pub fn step(&mut self, target: f32, dt: f32) {
+        let acceleration = self.stiffness * (target - self.angle) - self.damping * self.angular_velocity;
+        self.angular_velocity += acceleration * dt;
+        self.angle += self.angular_velocity * dt;
+    }
 }
 
 // ------------------------------------
@@ -213,6 +397,8 @@ pub struct Spawner {
     spawn_countdown: f32,
     sprite: SpriteRender,
     pub remaining: u32,
+    /// Whether the spawner has spawned anything yet.
+    pub started: bool,
 }
 
 impl Spawner {
@@ -222,20 +408,22 @@ impl Spawner {
             spawn_countdown: spawn_rate,
             sprite,
             remaining: 100,
+            started: false,
         }
     }
 
     /// Advance the spawn countdown by the given `time`.
-    /// Returns `true` if the countdown reached 0 and
-    /// was reset.
-    pub fn advance_and_reset(&mut self, time: f32) -> bool {
+    /// Returns the number of times the countdown reached 0
+    /// (and was reset) during that time, i.e. how many objects
+    /// should be spawned.
+    pub fn advance(&mut self, time: f32) -> u32 {
         self.spawn_countdown -= time;
-        if self.spawn_countdown <= 0.0 {
-            self.spawn_countdown = self.spawn_rate;
-            true
-        } else {
-            false
+        let mut spawns = 0;
+        while self.spawn_countdown <= 0.0 {
+            self.spawn_countdown += self.spawn_rate;
+            spawns += 1;
         }
+        spawns
     }
 
     pub fn sprite(&self) -> SpriteRender {
@@ -253,7 +441,9 @@ impl Component for Spawner {
 #[derive(Default)]
 pub struct Loading {
     progress: ProgressCounter,
-    sprite_sheet: Option<SpriteSheetHandle>,
+    sprite_sheets: HashMap<String, SpriteSheetHandle>,
+    audio: Option<(Sounds, Music)>,
+    screen: LoadingScreen,
 }
 impl Loading {
     pub fn new() -> Loading {
@@ -264,29 +454,52 @@ impl Loading {
 impl SimpleState for Loading {
     fn on_start(&mut self, data: StateData<GameData>) {
         let StateData { world, .. } = data;
-        let sprite_sheet = load_sprite_sheet(world, &mut self.progress);
-        self.sprite_sheet = Some(sprite_sheet);
+        info!(target: STATE_LOG_TARGET, "Loading assets");
+        record_gameplay_event(world, GameplayEvent::StateEntered { state: "loading" });
+
+        // the loading screen needs the camera too
+        init_camera(world);
+
+        let manifest = world.read_resource::<AssetManifest>().clone();
+        self.sprite_sheets = assets::load_sprite_sheets(world, &manifest, &mut self.progress);
+        if world.read_resource::<AudioConfig>().enabled {
+            self.audio = Some(audio::load_audio(world, &manifest, &mut self.progress));
+        }
+    }
+
+    fn on_stop(&mut self, data: StateData<GameData>) {
+        record_gameplay_event(data.world, GameplayEvent::StateExited { state: "loading" });
     }
 
-    fn update(&mut self, _data: &mut StateData<GameData>) -> SimpleTrans {
+    fn update(&mut self, data: &mut StateData<GameData>) -> SimpleTrans {
+        let delta_seconds = data.world.read_resource::<Time>().delta_seconds();
+        let arena = *data.world.read_resource::<Arena>();
+        self.screen.update(delta_seconds, &self.progress, &arena, &mut data.world.write_resource::<DebugLines>());
+
         match self.progress.complete() {
             Completion::Loading => {
-                println!("Loading...");
+                trace!(target: STATE_LOG_TARGET, "Loading... ({} of {} assets)", self.progress.num_finished(), self.progress.num_assets());
                 Trans::None
             },
-            Completion::Failed => {
-                println!("Loading of assets failed. I am slain :(");
-                Trans::Quit
-            },
-            Completion::Complete => {
-                if let Some(sprite_sheet) = &self.sprite_sheet {
-                    Trans::Switch(Box::new(Running {
-                        sprite_sheet: sprite_sheet.clone(),
-                    }))
-                } else {
-                    println!("false start :(");
-                    Trans::None
+            // keep the loading screen up for a moment, even if everything loaded straight away
+            _ if !self.screen.can_finish() => Trans::None,
+            completion => {
+                if let Completion::Failed = completion {
+                    // missing sprites are replaced with the built-in fallback ones,
+                    // and missing sounds just don't play
+                    assets::report_errors(&self.progress);
+                }
+                info!(target: STATE_LOG_TARGET, "Finished loading assets");
+
+                let manifest = data.world.read_resource::<AssetManifest>().clone();
+                let sprites = assets::resolve_sprites(data.world, &manifest, &self.sprite_sheets);
+                let animations = assets::resolve_animations(data.world, &manifest, &self.sprite_sheets);
+                data.world.add_resource(sprites);
+                data.world.add_resource(animations);
+                if let Some((sounds, music)) = self.audio.take() {
+                    audio::start_audio(data.world, sounds, music);
                 }
+                Trans::Switch(Box::new(Running))
             },
         }
     }
@@ -296,37 +509,66 @@ impl SimpleState for Loading {
 
 // ------------------------------------
 
-pub struct Running {
-    sprite_sheet: SpriteSheetHandle,
-}
+pub struct Running;
 
 impl SimpleState for Running {
     fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
         let StateData { world, .. } = data;
+        info!(target: STATE_LOG_TARGET, "Starting the game");
+        record_gameplay_event(world, GameplayEvent::StateEntered { state: "running" });
 
-        init_camera(world);
-        init_spawner(world, self.sprite_sheet.clone());
-        init_player(world, self.sprite_sheet.clone());
-        init_cursor(world, self.sprite_sheet.clone());
+        init_spawner(world);
+        init_player(world);
+        init_cursor(world);
+        init_letterbox(world);
     }
 
     fn on_stop(&mut self, data: StateData<GameData>) {
         let StateData { world, .. } = data;
-        println!("finishing SimpleState");
-
-        for co in world.read_resource::<EntityCollisionWorld>().collision_objects() {
-            println!("Leftover collision object - {}", co.handle().0)
-        }
+        info!(target: STATE_LOG_TARGET, "Stopping the game");
+        record_gameplay_event(world, GameplayEvent::StateExited { state: "running" });
+
+        // the dispatcher doesn't run again once the game stops, so a sync of its own takes
+        // the cleared colliders' objects out of the collision world
+        let mut colliders_sync = SyncExternalWorld::<Collider>::new();
+        colliders_sync.setup(&mut world.res);
+        world.write_storage::<Collider>().clear();
+        colliders_sync.run_now(&world.res);
     }
 }
 
+/// States run outside of the dispatcher, so anything they publish to the event channel
+/// after the last frame (e.g. when the game quits) would never reach the `GameplayLogSystem`.
+/// Their events are written to the log straight away instead.
+fn record_gameplay_event(world: &mut World, event: GameplayEvent) {
+    let (time, frame) = {
+        let time = world.read_resource::<Time>();
+        (time.absolute_time_seconds(), time.frame_number())
+    };
+    let mut log = world.write_resource::<GameplayLog>();
+    log.record(time, frame, &event);
+    log.flush();
+}
+
 // ------------------------------------
 
 pub struct Collider {
     shape: ShapeHandle<f32>,
     groups: CollisionGroups,
     query_type: GeometricQueryType<f32>,
+    /// goes up every time the shape, groups or query type are changed
+    revision: u32,
     pub(crate) handle: Option<CollisionObjectHandle>,
+    /// Whether the `SyncCollisionWorld` has moved the collision object to its entity yet.
+    pub(crate) positioned: bool,
+}
+/// Every setter bumps the revision, so the storage's `modify` guard only needs to compare that.
+impl DetectChanges for Collider {
+    type Snapshot = u32;
+
+    fn snapshot(&self) -> u32 { self.revision }
+
+    fn changed_since(&self, revision: &u32) -> bool { self.revision != *revision }
 }
 impl ToEvent<Option<CollisionObjectHandle>> for Collider {
     fn to_event(&self) -> Option<CollisionObjectHandle> {
@@ -336,6 +578,43 @@ impl ToEvent<Option<CollisionObjectHandle>> for Collider {
 impl Component for Collider {
     type Storage = RemovalFlaggedStorage<Self, Option<CollisionObjectHandle>>;
 }
+impl ExternalSync for Collider {
+    type World = EntityCollisionWorld;
+    type Handle = CollisionObjectHandle;
+
+    fn handle(&self) -> Option<CollisionObjectHandle> { self.handle }
+
+    fn set_handle(&mut self, handle: CollisionObjectHandle) {
+        self.handle = Some(handle);
+        self.positioned = false;
+    }
+
+    fn insert_into(&self, entity: Entity, world: &mut EntityCollisionWorld) -> CollisionObjectHandle {
+        // the SyncCollisionWorld system moves new objects to their entity's Transform,
+        // since it has to know which objects are new anyway
+        world
+            .add(
+                Isometry3::identity(),
+                self.shape.clone(),
+                self.groups.clone(),
+                self.query_type.clone(),
+                entity
+            )
+            .handle()
+    }
+
+    fn update_in(&self, handle: &CollisionObjectHandle, world: &mut EntityCollisionWorld) {
+        world.set_shape(*handle, self.shape.clone());
+        world.set_collision_groups(*handle, self.groups.clone());
+        world.set_query_type(*handle, self.query_type.clone());
+    }
+
+    fn remove_from(handles: &[CollisionObjectHandle], world: &mut EntityCollisionWorld) {
+        world.remove(handles);
+    }
+
+    fn revision(&self) -> Option<u32> { Some(self.revision) }
+}
 impl Collider {
     pub fn new(
         shape: ShapeHandle<f32>,
@@ -346,27 +625,77 @@ impl Collider {
             shape,
             groups,
             query_type,
+            revision: 0,
             handle: None,
+            positioned: false,
         }
     }
     pub fn shape(&self) -> &ShapeHandle<f32> { &self.shape }
     pub fn groups(&self) -> &CollisionGroups { &self.groups }
     pub fn query_type(&self) -> &GeometricQueryType<f32> { &self.query_type }
+
+    // Changes made with these setters are copied into the collision world by the
+    // `SyncCollisionWorld` system once they're reported, so make them through the storage's `modify`.
+
+    pub fn set_shape(&mut self, shape: ShapeHandle<f32>) {
+        self.shape = shape;
+        self.revision = self.revision.wrapping_add(1);
+    }
+    /// e.g. to stop a block from hitting the player twice
+    pub fn set_groups(&mut self, groups: CollisionGroups) {
+        self.groups = groups;
+        self.revision = self.revision.wrapping_add(1);
+    }
+    pub fn set_query_type(&mut self, query_type: GeometricQueryType<f32>) {
+        self.query_type = query_type;
+        self.revision = self.revision.wrapping_add(1);
+    }
 }
 
 // ------------------------------------
 
 pub type EntityCollisionWorld = CollisionWorld<f32, Entity>;
 
-pub type EntityContactEvent = (Entity, Entity, ContactEvent);
+/// A single point of contact between two colliders, in world coordinates.
+/// The `normal` points from the first entity towards the second,
+/// and `depth` is how far the two shapes are overlapping along it.
+#[derive(Debug, Clone, Copy)]
+pub struct ContactPoint {
+    pub point: Point3<f32>,
+    pub normal: Vector3<f32>,
+    pub depth: f32,
+}
+
+#[derive(Debug, Clone)]
+pub enum CollisionEventKind {
+    /// The two entities started touching this frame.
+    /// There are no contact points for contacts that are only within the collision world's
+    /// prediction margin so far, or that were found by sweeping a fast-moving collider along
+    /// its path. Swept contacts were already over by the end of the frame, so they're followed
+    /// by a `ContactStopped` in the same frame.
+    ContactStarted(Vec<ContactPoint>),
+    /// The two entities were already touching, and still are.
+    ContactPersisting(Vec<ContactPoint>),
+    /// The two entities stopped touching this frame.
+    ContactStopped,
+    /// A proximity-only collider started intersecting with the other entity.
+    ProximityEntered,
+    /// A proximity-only collider stopped intersecting with the other entity.
+    ProximityExited,
+}
+
+/// Event written to the `EventChannel<EntityCollisionEvent>` by the `SyncCollisionWorld` system.
+#[derive(Debug, Clone)]
+pub struct EntityCollisionEvent {
+    pub entity1: Entity,
+    pub entity2: Entity,
+    pub kind: CollisionEventKind,
+}
 
 // ------------------------------------
 
-fn init_spawner(world: &mut World, sprite_sheet: SpriteSheetHandle) {
-    let sprite = SpriteRender {
-        sprite_sheet,
-        sprite_number: 0,
-    };
+fn init_spawner(world: &mut World) {
+    let sprite = world.read_resource::<Sprites>().sprite("block");
     world.create_entity()
         .with(Spawner::new(0.03, sprite))
         .build();
@@ -374,66 +703,63 @@ fn init_spawner(world: &mut World, sprite_sheet: SpriteSheetHandle) {
 
 // ------------------------------------
 
-fn load_sprite_sheet<P: Progress>(world: &mut World, progress: P) -> SpriteSheetHandle {
-    let texture_handle = {
-        let loader = world.read_resource::<Loader>();
-        let texture_storage = world.read_resource::<AssetStorage<Texture>>();
-        loader.load(
-            "texture/falldown_spritesheet.png",
-            PngFormat,
-            TextureMetadata::srgb_scale(),
-            progress,
-            &texture_storage,
-        )
-    };
+fn init_letterbox(world: &mut World) {
+    let sprite = world.read_resource::<Sprites>().sprite("pixel");
 
-    let loader = world.read_resource::<Loader>();
-    let sprite_sheet_store = world.read_resource::<AssetStorage<SpriteSheet>>();
-    loader.load(
-        "texture/falldown_spritesheet.ron",
-        SpriteSheetFormat,
-        texture_handle,
-        (),
-        &sprite_sheet_store,
-    )
+    // the ArenaCameraSystem moves these into place
+    for side in [BarSide::Left, BarSide::Right, BarSide::Bottom, BarSide::Top].iter() {
+        world.create_entity()
+            .with(LetterboxBar(*side))
+            .with(Transform::default())
+            .with(sprite.clone())
+            .with(Rgba(0.1, 0.1, 0.1, 1.0))
+            .build();
+    }
 }
 
 // ------------------------------------
 
 fn init_camera(world: &mut World) {
+    let arena = *world.read_resource::<Arena>();
     let mut transform = Transform::default();
     transform.set_z(1.0);
     world.create_entity()
-        .with(Camera::from(Projection::orthographic(0.0, ARENA_WIDTH, 0.0, ARENA_HEIGHT)))
+        // the ArenaCameraSystem fits the projection to the arena and the window
+        .with(Camera::from(Projection::orthographic(0.0, arena.width, 0.0, arena.height)))
+        .with(CameraController::default())
         .with(transform)
         .build();
 }
 
 // ------------------------------------
 
-fn init_player(world: &mut World, sprite_sheet: SpriteSheetHandle) {
+fn init_player(world: &mut World) {
+    let arena = *world.read_resource::<Arena>();
     let mut transform = Transform::default();
-    transform.set_xyz(ARENA_WIDTH * 0.5, ARENA_HEIGHT * 0.15, 0.1);
+    transform.set_xyz(arena.width * 0.5, arena.height * 0.15, 0.1);
 
-    let sprite = SpriteRender {
-        sprite_sheet,
-        sprite_number: 1, // player sprite
-    };
+    let sprite = world.read_resource::<Sprites>().sprite("player");
 
     let color = thread_rng().gen::<ColorType>();
+    let groups = world.read_resource::<CollisionLayers>().groups("player");
 
     // Player
     let player = world.create_entity()
         .with(Player::new())
         .with(Affiliation::Player(color))
         .with(FollowMouse {
-            x_ratio: 0.9,
-            y_ratio: 0.0,
+            half_life: 0.03,
+            max_speed: Some(1200.0),
+            dead_zone: 0.5,
+            // the margin keeps all of the player inside the arena
+            bounds: FollowBounds { margin: 15.0, ..Default::default() },
+            in_player_zone: true,
+            ..Default::default()
         })
         .with(transform)
         .with(Collider::new(
             ShapeHandle::new(Ball::new(15f32)),
-            player_collision_group(),
+            groups,
             GeometricQueryType::Contacts(0f32, 0f32),
         ))
         .build();
@@ -444,29 +770,33 @@ fn init_player(world: &mut World, sprite_sheet: SpriteSheetHandle) {
     world.create_entity()
         .with(inner_transform)
         .with(Transparent)
+        .with(SpriteAnimation::new(Arc::new(Animation::still(sprite.clone()))))
         .with(sprite)
         .with(Parent { entity: player })
         .build();
+
+    // the camera follows the player up and down tall arenas
+    for controller in (&mut world.write_storage::<CameraController>()).join() {
+        controller.follow = Some(player);
+    }
 }
 
 // ------------------------------------
 
-fn init_cursor(world: &mut World, sprite_sheet: SpriteSheetHandle) {
-    let sprite = SpriteRender {
-        sprite_sheet,
-        sprite_number: 2, // 1 pixel thing
-    };
+fn init_cursor(world: &mut World) {
+    let arena = *world.read_resource::<Arena>();
+    let sprite = world.read_resource::<Sprites>().sprite("pixel");
 
     // Vertical Line that only follows the X position of the mouse
     {
         let mut transform = Transform::default();
-        transform.set_scale(1.0, ARENA_HEIGHT, 1.0);
-        transform.set_x(ARENA_WIDTH * 0.5);
-        transform.set_y(ARENA_HEIGHT * 0.5);
+        transform.set_scale(1.0, arena.height, 1.0);
+        transform.set_x(arena.width * 0.5);
+        transform.set_y(arena.height * 0.5);
 
         let follow_mouse = FollowMouse {
-            x_ratio: 1.0,
             y_ratio: 0.0,
+            ..Default::default()
         };
 
         world.create_entity()
@@ -474,18 +804,19 @@ fn init_cursor(world: &mut World, sprite_sheet: SpriteSheetHandle) {
             .with(Transparent)
             .with(sprite.clone())
             .with(follow_mouse)
+            .with(ArenaSpan::Vertical)
             .build();
     }
 
     // Horizontal Line that only follows the Y position of the mouse
     {
         let mut transform = Transform::default();
-        transform.set_scale(ARENA_WIDTH, 1.0, 1.0);
-        transform.set_xyz(ARENA_WIDTH * 0.5, ARENA_HEIGHT * 0.5, 0.0);
+        transform.set_scale(arena.width, 1.0, 1.0);
+        transform.set_xyz(arena.width * 0.5, arena.height * 0.5, 0.0);
 
         let follow_mouse = FollowMouse {
             x_ratio: 0.0,
-            y_ratio: 1.0,
+            ..Default::default()
         };
 
         world.create_entity()
@@ -493,6 +824,7 @@ fn init_cursor(world: &mut World, sprite_sheet: SpriteSheetHandle) {
             .with(Transparent)
             .with(sprite.clone())
             .with(follow_mouse)
+            .with(ArenaSpan::Horizontal)
             .build();
     }
 }
\ No newline at end of file
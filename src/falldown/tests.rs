@@ -0,0 +1,146 @@
+use super::*;
+
+fn assert_close((x, y): (f32, f32), (expected_x, expected_y): (f32, f32)) {
+    assert!(
+        (x - expected_x).abs() < 1e-3 && (y - expected_y).abs() < 1e-3,
+        "expected ({}, {}), got ({}, {})", expected_x, expected_y, x, y
+    );
+}
+
+#[test]
+fn follow_mouse_without_half_life_snaps_to_the_mouse() {
+    let follower = FollowMouse::default();
+
+    assert_close(follower.step((0.0, 0.0), (30.0, 40.0), 1.0 / 60.0), (30.0, 40.0));
+}
+
+#[test]
+fn follow_mouse_ignores_axes_with_zero_ratio() {
+    let follower = FollowMouse { y_ratio: 0.0, ..Default::default() };
+
+    assert_close(follower.step((0.0, 0.0), (30.0, 40.0), 1.0 / 60.0), (30.0, 0.0));
+}
+
+#[test]
+fn follow_mouse_halves_the_distance_every_half_life() {
+    let follower = FollowMouse { half_life: 0.1, ..Default::default() };
+
+    assert_close(follower.step((0.0, 0.0), (80.0, -40.0), 0.1), (40.0, -20.0));
+    assert_close(follower.step((0.0, 0.0), (80.0, -40.0), 0.2), (60.0, -30.0));
+}
+
+#[test]
+fn follow_mouse_is_the_same_at_any_frame_rate() {
+    let follower = FollowMouse { half_life: 0.05, ..Default::default() };
+    let target = (100.0, 50.0);
+
+    let mut slow = (0.0, 0.0);
+    for _ in 0..3 {
+        slow = follower.step(slow, target, 1.0 / 30.0);
+    }
+    let mut fast = (0.0, 0.0);
+    for _ in 0..12 {
+        fast = follower.step(fast, target, 1.0 / 120.0);
+    }
+
+    assert_close(slow, fast);
+}
+
+#[test]
+fn follow_mouse_ratio_scales_the_half_life() {
+    let follower = FollowMouse { x_ratio: 0.5, half_life: 0.1, ..Default::default() };
+
+    // x takes twice as long to halve its distance as y
+    assert_close(follower.step((0.0, 0.0), (80.0, 80.0), 0.2), (40.0, 60.0));
+}
+
+#[test]
+fn follow_mouse_is_limited_to_max_speed() {
+    let follower = FollowMouse { max_speed: Some(100.0), ..Default::default() };
+
+    assert_close(follower.step((0.0, 0.0), (300.0, 400.0), 0.1), (6.0, 8.0));
+    // close enough to get there within the limit
+    assert_close(follower.step((0.0, 0.0), (3.0, 4.0), 0.1), (3.0, 4.0));
+}
+
+#[test]
+fn follow_mouse_stays_put_within_dead_zone() {
+    let follower = FollowMouse { dead_zone: 5.0, ..Default::default() };
+
+    assert_close(follower.step((0.0, 0.0), (3.0, 4.0), 0.1), (0.0, 0.0));
+    assert_close(follower.step((0.0, 0.0), (3.0, 4.5), 0.1), (3.0, 4.5));
+}
+
+#[test]
+fn follow_mouse_dead_zone_only_counts_followed_axes() {
+    let follower = FollowMouse { y_ratio: 0.0, dead_zone: 5.0, ..Default::default() };
+
+    // far away vertically, but within the dead zone horizontally
+    assert_close(follower.step((0.0, 0.0), (4.0, 100.0), 0.1), (0.0, 0.0));
+}
+
+#[test]
+fn follow_mouse_in_zone_takes_the_zone_but_keeps_its_margin() {
+    let follower = FollowMouse {
+        half_life: 0.1,
+        bounds: FollowBounds { margin: 5.0, max_y: Some(0.9), ..Default::default() },
+        in_player_zone: true,
+        ..Default::default()
+    };
+    let zoned = follower.in_zone(&PlayerZone { y_ratio: 0.5, band: 0.2 });
+
+    assert_eq!(zoned.y_ratio, 0.5);
+    assert_eq!(zoned.bounds, FollowBounds { margin: 5.0, ..FollowBounds::bottom_band(0.2) });
+    assert_eq!(zoned.half_life, 0.1);
+}
+
+fn arena(width: f32, height: f32) -> Arena {
+    Arena { width, height, ..Default::default() }
+}
+
+#[test]
+fn follow_bounds_keep_inside_the_arena() {
+    let bounds = FollowBounds::default();
+
+    assert_close(bounds.clamp((-10.0, 150.0), &arena(200.0, 100.0)), (0.0, 100.0));
+    assert_close(bounds.clamp((50.0, 60.0), &arena(200.0, 100.0)), (50.0, 60.0));
+}
+
+#[test]
+fn follow_bounds_keep_away_from_the_edges_by_the_margin() {
+    let bounds = FollowBounds { margin: 5.0, ..Default::default() };
+
+    assert_close(bounds.clamp((-10.0, 150.0), &arena(200.0, 100.0)), (5.0, 95.0));
+}
+
+#[test]
+fn follow_bounds_margin_wider_than_the_arena_leaves_the_middle() {
+    let bounds = FollowBounds { margin: 80.0, ..Default::default() };
+
+    assert_close(bounds.clamp((0.0, 0.0), &arena(100.0, 200.0)), (50.0, 80.0));
+}
+
+#[test]
+fn follow_bounds_are_fractions_of_the_arena() {
+    let bounds = FollowBounds {
+        min_x: Some(0.25),
+        max_x: Some(0.75),
+        ..FollowBounds::bottom_band(0.3)
+    };
+
+    assert_close(bounds.clamp((0.0, 80.0), &arena(200.0, 100.0)), (50.0, 30.0));
+    // the same bounds after the arena is resized
+    assert_close(bounds.clamp((0.0, 80.0), &arena(400.0, 200.0)), (100.0, 60.0));
+    assert_close(bounds.clamp((390.0, 10.0), &arena(400.0, 200.0)), (300.0, 10.0));
+}
+
+#[test]
+fn follow_bounds_past_the_arena_give_way_to_its_edges() {
+    let bounds = FollowBounds {
+        min_x: Some(-1.0),
+        max_y: Some(2.0),
+        ..Default::default()
+    };
+
+    assert_close(bounds.clamp((-50.0, 500.0), &arena(200.0, 100.0)), (0.0, 100.0));
+}
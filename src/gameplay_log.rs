@@ -0,0 +1,134 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use amethyst::ecs::prelude::Entity;
+use log::{info, warn};
+use serde::Serialize;
+
+use crate::falldown::ColorType;
+
+/// Target used for diagnostics about the gameplay log itself.
+const LOG_TARGET: &str = "falldown::gameplay_log";
+
+/// Identifies an entity in the gameplay log. Entity ids are reused once an entity
+/// is deleted, so the generation is needed to tell apart the entities sharing an id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EntityRef {
+    pub id: u32,
+    pub gen: i32,
+}
+
+impl From<Entity> for EntityRef {
+    fn from(entity: Entity) -> Self {
+        EntityRef { id: entity.id(), gen: entity.gen().id() }
+    }
+}
+
+/// Something that happened during a run which is worth recording for later analysis.
+/// Systems publish these to an `EventChannel<GameplayEvent>`, and the `GameplayLogSystem`
+/// writes them to the `GameplayLog`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameplayEvent {
+    /// Written once, when the log is opened.
+    RunStarted { unix_time_ms: u128 },
+    StateEntered { state: &'static str },
+    StateExited { state: &'static str },
+    /// A spawner started dropping a wave of `blocks` blocks.
+    WaveStarted { blocks: u32 },
+    Spawned { block: EntityRef, color: ColorType, x: f32, fall_rate: f32 },
+    /// The player touched a block of its own color, and collected it.
+    Caught { block: EntityRef, color: ColorType },
+    /// The player ran into a block of a different color.
+    Hit { block: EntityRef, color: ColorType },
+    /// A block fell off the bottom of the arena without being caught.
+    Missed { block: EntityRef, color: Option<ColorType> },
+    /// The player caught `streak` blocks in a row without running into one.
+    PoweredUp { streak: u32 },
+}
+
+/// One line of the log.
+#[derive(Serialize)]
+struct Record<'a> {
+    /// Seconds since the game started.
+    time: f64,
+    frame: u64,
+    #[serde(flatten)]
+    event: &'a GameplayEvent,
+}
+
+// ------------------------------------
+
+/// Resource that writes `GameplayEvent`s to a file, one JSON object per line.
+/// A log that couldn't be opened (or written to) quietly discards everything it's given,
+/// so a read-only install still runs.
+#[derive(Default)]
+pub struct GameplayLog {
+    writer: Option<BufWriter<File>>,
+}
+
+impl GameplayLog {
+    /// Create a new log file at `path`, replacing any existing one.
+    pub fn create<P: AsRef<Path>>(path: P) -> GameplayLog {
+        let path = path.as_ref();
+        let file = path.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| File::create(path));
+
+        let mut log = match file {
+            Ok(file) => {
+                info!(target: LOG_TARGET, "Recording gameplay events to {}", path.display());
+                GameplayLog { writer: Some(BufWriter::new(file)) }
+            },
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Couldn't create gameplay log {}: {}", path.display(), e);
+                GameplayLog::default()
+            },
+        };
+
+        let unix_time_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_millis());
+        log.record(0.0, 0, &GameplayEvent::RunStarted { unix_time_ms });
+        log
+    }
+
+    /// Append an event that happened at `time` seconds into the game, on the given frame.
+    pub fn record(&mut self, time: f64, frame: u64, event: &GameplayEvent) {
+        let result = match self.writer.as_mut() {
+            Some(writer) => serde_json::to_writer(&mut *writer, &Record { time, frame, event })
+                .map_err(|e| e.to_string())
+                .and_then(|_| writeln!(writer).map_err(|e| e.to_string())),
+            None => return,
+        };
+        if let Err(e) = result {
+            self.disable(&e);
+        }
+    }
+
+    /// Write out any buffered events.
+    pub fn flush(&mut self) {
+        let result = match self.writer.as_mut() {
+            Some(writer) => writer.flush(),
+            None => return,
+        };
+        if let Err(e) = result {
+            self.disable(&e.to_string());
+        }
+    }
+
+    fn disable(&mut self, reason: &str) {
+        warn!(target: LOG_TARGET, "Gameplay log stopped after a write failed: {}", reason);
+        self.writer = None;
+    }
+}
+
+impl Drop for GameplayLog {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
@@ -0,0 +1,85 @@
+use amethyst::{
+    assets::ProgressCounter,
+    core::nalgebra::Point3,
+    renderer::{DebugLines, Rgba},
+};
+
+use crate::arena::Arena;
+
+/// The loading screen stays up for at least this long, even if everything loads
+/// straight away, so it doesn't just flicker on and off.
+pub const MIN_DISPLAY_SECONDS: f32 = 0.75;
+
+/// How much of the arena's width the bar takes up.
+const BAR_WIDTH_FRACTION: f32 = 0.6;
+const BAR_HEIGHT: f32 = 8.0;
+/// Gap between the outline and the filled part of the bar.
+const BAR_PADDING: f32 = 1.5;
+/// The filled part of the bar is drawn as horizontal lines this far apart.
+const FILL_SPACING: f32 = 0.5;
+const BAR_Z: f32 = 0.5;
+
+/// Draws a progress bar for a `ProgressCounter` with debug lines, since no sprites
+/// are available until loading is done.
+#[derive(Default)]
+pub struct LoadingScreen {
+    elapsed: f32,
+    shown_progress: f32,
+}
+
+impl LoadingScreen {
+    /// How much of the loading is done, from 0 to 1.
+    pub fn progress(progress: &ProgressCounter) -> f32 {
+        match progress.num_assets() {
+            0 => 1.0,
+            total => progress.num_finished() as f32 / total as f32,
+        }
+    }
+
+    /// Whether the screen has been up for long enough to be taken down.
+    pub fn can_finish(&self) -> bool {
+        self.elapsed >= MIN_DISPLAY_SECONDS
+    }
+
+    /// Advance the screen by `delta_seconds`, and draw it.
+    pub fn update(&mut self, delta_seconds: f32, progress: &ProgressCounter, arena: &Arena, lines: &mut DebugLines) {
+        self.elapsed += delta_seconds;
+
+        // the bar fills up over the minimum display time at the quickest,
+        // so it doesn't jump straight to full
+        let time_progress = (self.elapsed / MIN_DISPLAY_SECONDS).min(1.0);
+        self.shown_progress = LoadingScreen::progress(progress).min(time_progress).max(self.shown_progress);
+
+        self.draw(arena, lines);
+    }
+
+    fn draw(&self, arena: &Arena, lines: &mut DebugLines) {
+        let bar_width = arena.width * BAR_WIDTH_FRACTION;
+        let left = (arena.width - bar_width) / 2.0;
+        let bottom = (arena.height - BAR_HEIGHT) / 2.0;
+        let (right, top) = (left + bar_width, bottom + BAR_HEIGHT);
+
+        let outline = Rgba(0.2, 0.2, 0.2, 1.0);
+        let fill = Rgba(0.000, 0.749, 1.000, 1.0);
+
+        let line = |lines: &mut DebugLines, (x1, y1): (f32, f32), (x2, y2): (f32, f32), color: Rgba| {
+            lines.draw_line(Point3::new(x1, y1, BAR_Z), Point3::new(x2, y2, BAR_Z), color);
+        };
+
+        line(lines, (left, bottom), (right, bottom), outline);
+        line(lines, (right, bottom), (right, top), outline);
+        line(lines, (right, top), (left, top), outline);
+        line(lines, (left, top), (left, bottom), outline);
+
+        let fill_left = left + BAR_PADDING;
+        let fill_right = fill_left + (bar_width - 2.0 * BAR_PADDING) * self.shown_progress;
+        if fill_right <= fill_left {
+            return;
+        }
+        let mut y = bottom + BAR_PADDING;
+        while y <= top - BAR_PADDING {
+            line(lines, (fill_left, y), (fill_right, y), fill);
+            y += FILL_SPACING;
+        }
+    }
+}
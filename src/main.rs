@@ -1,23 +1,48 @@
 extern crate amethyst;
 extern crate hibitset;
+extern crate log;
 extern crate nalgebra as na;
 extern crate ncollide3d;
 extern crate rand;
+extern crate serde;
+extern crate serde_json;
 
+mod animation;
+mod arena;
+mod assets;
+mod audio;
+mod camera;
+mod collision_layers;
+mod external_sync;
 mod falldown;
+mod gameplay_log;
+mod loading_screen;
+mod mouse;
+mod particles;
 mod storage;
 mod systems;
+mod timing;
 mod util;
 
-use crate::falldown::Loading;
+use crate::arena::Arena;
+use crate::assets::AssetManifest;
+use crate::audio::{AudioConfig, Music};
+use crate::collision_layers::{CollisionLayerConfig, CollisionLayers};
+use crate::falldown::{Collider, Loading};
+use crate::gameplay_log::GameplayLog;
+
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use amethyst::{
+    audio::AudioBundle,
     core:: {
         transform::TransformBundle,
     },
     input::InputBundle,
     prelude::*,
-    renderer::{DisplayConfig, DrawFlat2D, Pipeline, RenderBundle, Stage},
+    renderer::{
+        DebugLines, DebugLinesParams, DisplayConfig, DrawDebugLines, DrawFlat2D, Pipeline, PosColorNorm, RenderBundle, Stage,
+    },
     utils::application_root_dir,
 };
 
@@ -28,13 +53,34 @@ fn main() -> amethyst::Result<()> {
 
     let config = DisplayConfig::load(app_root.join("resources/display_config.ron"));
 
+    let collision_layer_config = CollisionLayerConfig::load(app_root.join("resources/collision_layers.ron"));
+    let collision_layers = CollisionLayers::from_config(&collision_layer_config)
+        .unwrap_or_else(|e| panic!("Invalid collision layer config: {}", e));
+
+    let arena = Arena::load(app_root.join("resources/arena.ron"));
+
+    let assets_directory = app_root.join("assets");
+
+    let mut asset_manifest = AssetManifest::load(app_root.join("resources/asset_manifest.ron"));
+    asset_manifest.load_animations(&assets_directory);
+
+    let audio_config = AudioConfig::load(app_root.join("resources/audio.ron"));
+
+    let collision_debug_config = systems::CollisionDebugConfig::load(app_root.join("resources/collision_debug.ron"));
+
+    // each run gets its own log, named after when it started
+    let started_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since_epoch| since_epoch.as_secs());
+    let gameplay_log = GameplayLog::create(app_root.join("logs").join(format!("gameplay-{}.jsonl", started_at)));
+
     let pipe = Pipeline::build().with_stage(
         Stage::with_backbuffer()
             .clear_target([1., 1., 1., 1.], 1.0)
             .with_pass(DrawFlat2D::new())
+            .with_pass(DrawDebugLines::<PosColorNorm>::new())
     );
 
-    let game_data = GameDataBuilder::default()
+    let mut game_data = GameDataBuilder::default()
+        .with(systems::FixedTimestepSystem, "fixed_timestep", &[])
         .with_bundle(InputBundle::<String, String>::new())?
         .with_bundle(systems::PlayerControlBundle::<String, String>::new())?
         .with_bundle(TransformBundle::new())?
@@ -42,14 +88,35 @@ fn main() -> amethyst::Result<()> {
             .with_sprite_sheet_processor()
             .with_sprite_visibility_sorting(&["transform_system"])
         )?
-        .with(systems::SpawnerSystem, "spawner", &[])
-        .with(systems::FallingObjectSystem, "falling_objects", &["spawner"])
-        .with(systems::SyncCollisionWorld::new(), "sync_collision", &[])
+        .with(systems::ArenaCameraSystem::new(), "arena_camera", &[])
+        .with(systems::SpawnerSystem, "spawner", &["fixed_timestep"])
+        .with(systems::FallingObjectSystem, "falling_objects", &["fixed_timestep", "spawner"])
+        .with(systems::SyncExternalWorld::<Collider>::new(), "sync_colliders", &[])
+        .with(systems::SyncCollisionWorld::new(), "sync_collision", &["sync_colliders"])
         .with(systems::ObjectCollection::new(), "object_collection", &["sync_collision"])
+        .with(systems::CollisionDebugSystem::<String, String>::new(), "collision_debug", &["sync_collision"])
+        .with(systems::ParticleSystem::new(), "particles", &["falling_objects", "object_collection"])
+        .with(systems::SpriteAnimationSystem, "sprite_animation", &["spawner", "object_collection"])
+        .with(systems::SoundEffectSystem::new(), "sound_effects", &["spawner", "falling_objects", "object_collection"])
+        .with(systems::GameplayLogSystem::new(), "gameplay_log", &["spawner", "falling_objects", "object_collection"])
     ;
 
-    let assets_directory = app_root.join("assets");
-    let mut game = Application::new(assets_directory, Loading::new(), game_data)?;
+    // without an audio device, the bundle just doesn't play anything
+    if audio_config.enabled {
+        game_data = game_data.with_bundle(AudioBundle::new(|music: &mut Music| music.next_track()))?;
+    }
+
+    let mut game = Application::build(assets_directory, Loading::new())?
+        // the debug lines pass draws the loading screen (and the collision debug view)
+        .with_resource(DebugLines::new())
+        .with_resource(DebugLinesParams { line_width: 1.0 })
+        .with_resource(arena)
+        .with_resource(asset_manifest)
+        .with_resource(audio_config)
+        .with_resource(collision_layers)
+        .with_resource(collision_debug_config)
+        .with_resource(gameplay_log)
+        .build(game_data)?;
 
     game.run();
 
@@ -0,0 +1,39 @@
+use amethyst::{
+    core::{
+        nalgebra::{Point3, Vector4},
+        transform::GlobalTransform,
+    },
+    renderer::Camera,
+};
+
+/// Resource with the mouse's position in world space, as seen through the active camera.
+/// It's `None` while the mouse is outside of the window, or there's no camera.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MouseWorldPosition {
+    pub position: Option<Point3<f32>>,
+}
+
+/// Find the point in the world under a position on the screen (in pixels from the top-left corner),
+/// by undoing the camera's projection and transform. The point is on the camera's near plane,
+/// which for an orthographic camera has the same x and y as anything else under that position.
+pub fn screen_to_world(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    (pixel_x, pixel_y): (f32, f32),
+    screen_width: f32,
+    screen_height: f32,
+) -> Option<Point3<f32>> {
+    // normalized device coordinates go from -1 to 1, with y pointing up
+    let ndc = Vector4::new(
+        (pixel_x / screen_width) * 2.0 - 1.0,
+        1.0 - (pixel_y / screen_height) * 2.0,
+        -1.0,
+        1.0,
+    );
+    let view = camera.proj.try_inverse()? * ndc;
+    let world = camera_transform.0 * view;
+    if world.w == 0.0 {
+        return None;
+    }
+    Some(Point3::new(world.x / world.w, world.y / world.w, world.z / world.w))
+}
@@ -0,0 +1,100 @@
+use std::f32::consts::PI;
+
+use amethyst::{
+    core::nalgebra::Vector3,
+    ecs::prelude::{Component, DenseVecStorage, VecStorage},
+    renderer::Rgba,
+};
+
+/// A one-off burst of particles. Entities with one of these are deleted by the
+/// `ParticleSystem` once their particles have been emitted, so an effect can be
+/// triggered by just creating an entity with an emitter.
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    pub position: Vector3<f32>,
+    pub count: u32,
+    pub color: Rgba,
+    /// The direction the particles are thrown in, as an angle from the x axis.
+    pub direction: f32,
+    /// How far (either way) from `direction` a particle's direction can be.
+    pub spread: f32,
+    /// Smallest and largest speed, in arena units per second.
+    pub speed: (f32, f32),
+    /// Shortest and longest lifetime, in seconds.
+    pub lifetime: (f32, f32),
+    /// Particles start out this big, and shrink away over their lifetime.
+    pub size: f32,
+}
+
+impl Component for ParticleEmitter {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl ParticleEmitter {
+    /// A burst in every direction, for a caught block.
+    pub fn catch(position: Vector3<f32>, color: Rgba) -> ParticleEmitter {
+        ParticleEmitter {
+            position,
+            count: 24,
+            color,
+            direction: 0.0,
+            spread: PI,
+            speed: (40.0, 100.0),
+            lifetime: (0.3, 0.6),
+            size: 2.5,
+        }
+    }
+
+    /// A smaller, slower burst, for a block the player ran into.
+    pub fn hit(position: Vector3<f32>, color: Rgba) -> ParticleEmitter {
+        ParticleEmitter {
+            count: 10,
+            speed: (15.0, 40.0),
+            lifetime: (0.2, 0.4),
+            size: 2.0,
+            ..ParticleEmitter::catch(position, color)
+        }
+    }
+
+    /// A puff thrown upwards, for a block that hit the bottom of the arena.
+    pub fn miss(position: Vector3<f32>, color: Rgba) -> ParticleEmitter {
+        ParticleEmitter {
+            count: 12,
+            direction: PI / 2.0,
+            spread: PI / 3.0,
+            speed: (30.0, 70.0),
+            lifetime: (0.25, 0.5),
+            size: 1.5,
+            ..ParticleEmitter::catch(position, color)
+        }
+    }
+}
+
+// ------------------------------------
+
+/// A single particle. Particles are pooled, so once one has run out of
+/// lifetime it is hidden, rather than deleted, until it's needed again.
+#[derive(Debug)]
+pub struct Particle {
+    pub velocity: Vector3<f32>,
+    pub age: f32,
+    pub lifetime: f32,
+    pub size: f32,
+    /// The color the particle started out with, before it started fading.
+    pub color: Rgba,
+}
+
+impl Component for Particle {
+    type Storage = VecStorage<Self>;
+}
+
+impl Particle {
+    pub fn is_alive(&self) -> bool {
+        self.age < self.lifetime
+    }
+
+    /// How far through its lifetime the particle is, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        if self.lifetime > 0.0 { (self.age / self.lifetime).min(1.0) } else { 1.0 }
+    }
+}
@@ -1,24 +1,55 @@
 use amethyst::ecs::prelude::*;
 use amethyst::ecs::storage::{TryDefault, UnprotectedStorage};
-use amethyst::shrev::{ Event, EventChannel};
+use amethyst::ecs::world::EntitiesRes;
+use amethyst::shrev::{ Event, EventChannel, EventIterator};
 use hibitset::BitSetLike;
-use std::ops::DerefMut;
+use std::ops::{Deref, DerefMut};
 use amethyst::ecs::storage::MaskedStorage;
 use amethyst::ecs::world::Index;
 use std::marker::PhantomData;
 
+#[cfg(test)]
+mod tests;
+
 pub trait RemovalTracked<C> {
     fn removal_channel(&self) -> &EventChannel<DetailedComponentEvent<C>>;
 
     fn removal_channel_mut(&mut self) -> &mut EventChannel<DetailedComponentEvent<C>>;
 }
 
+pub trait MarkModified {
+    /// Remember that the component at `id` was changed. No matter how many times
+    /// this is called, only one `Modified` event is written for the `id` by the
+    /// next `flush_events`.
+    fn mark_modified(&mut self, id: Index);
+
+    /// Write the events for everything that happened since the last flush to the channel.
+    /// The `entities` are used to find out which entity (and generation) each event belongs to.
+    fn flush_events(&mut self, entities: &EntitiesRes);
+}
+
 // -----------------
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum DetailedComponentEvent<T> {
-    Inserted(Index),
-    Modified(Index),
-    Removed(Index, T),
+    Inserted(Entity),
+    Modified(Entity),
+    Removed(Entity, T),
+    /// Every component in the storage was removed at once, e.g. because the world is being dropped.
+    /// This is written to the channel straight away, since there may not be another flush.
+    Cleared(Vec<(Entity, T)>),
+}
+
+impl<T> DetailedComponentEvent<T> {
+    /// The entity the event is about, unless it's a `Cleared` event.
+    pub fn entity(&self) -> Option<Entity> {
+        match *self {
+            DetailedComponentEvent::Inserted(entity) => Some(entity),
+            DetailedComponentEvent::Modified(entity) => Some(entity),
+            DetailedComponentEvent::Removed(entity, _) => Some(entity),
+            DetailedComponentEvent::Cleared(_) => None,
+        }
+    }
 }
 
 // -----------------
@@ -35,9 +66,25 @@ where
 
 // -----------------
 
+/// Storage wrapper that reports insertions, modifications and removals through an event channel.
+/// Unlike specs' `FlaggedStorage`, removal events carry some data (`E`) from the removed component,
+/// e.g. a handle into some external world, or a full snapshot of the component.
+///
+/// Mutable access alone doesn't count as a modification; use `mark_modified` or `modify`
+/// (from `RemovalBroadcaster`) to report changes.
+///
+/// The underlying storage only knows about entity ids, not generations, so events are buffered
+/// until `flush_events` is called, at which point they are matched up with their `Entity`.
+/// Modifications are coalesced, so readers see at most one `Modified` event per entity per flush,
+/// and components that are inserted and removed again between two flushes aren't reported at all.
 pub struct RemovalFlaggedStorage<C, E = C, T = DenseVecStorage<C>> {
     phantom: PhantomData<C>,
     channel: EventChannel<DetailedComponentEvent<E>>,
+    /// the entity that owned the component at each id, as of the last flush
+    owners: Vec<Option<Entity>>,
+    inserted: BitSet,
+    modified: BitSet,
+    removed: Vec<(Entity, E)>,
     storage: T,
 }
 
@@ -51,6 +98,10 @@ where
         RemovalFlaggedStorage {
             phantom: PhantomData,
             channel: EventChannel::<DetailedComponentEvent<E>>::new(),
+            owners: Vec::new(),
+            inserted: BitSet::new(),
+            modified: BitSet::new(),
+            removed: Vec::new(),
             storage: T::unwrap_default(),
         }
     }
@@ -63,23 +114,43 @@ where
     T: UnprotectedStorage<C>
 {
 //impl<C: Component + ToEvent<E>, E, T: UnprotectedStorage<C>> UnprotectedStorage<C> for RemovalFlaggedStorage<C, E, T> {
-    unsafe fn clean<B: BitSetLike>(&mut self, has: B) { self.storage.clean(has); }
+    unsafe fn clean<B: BitSetLike>(&mut self, has: B) {
+        let mut cleared = Vec::new();
+        for id in (&has).iter() {
+            self.inserted.remove(id);
+            self.modified.remove(id);
+            if let Some(owner) = self.owners.get_mut(id as usize).and_then(Option::take) {
+                cleared.push((owner, self.storage.get(id).to_event()));
+            }
+        }
+        self.storage.clean(has);
+
+        let removed = self.removed.drain(..)
+            .map(|(entity, data)| DetailedComponentEvent::Removed(entity, data));
+        self.channel.iter_write(removed);
+        if !cleared.is_empty() {
+            self.channel.single_write(DetailedComponentEvent::Cleared(cleared));
+        }
+    }
 
     unsafe fn get(&self, id: u32) -> &C { self.storage.get(id) }
 
-    unsafe fn get_mut(&mut self, id: u32) -> &mut C {
-        self.channel.single_write(DetailedComponentEvent::Modified(id));
-        self.storage.get_mut(id)
-    }
+    unsafe fn get_mut(&mut self, id: u32) -> &mut C { self.storage.get_mut(id) }
 
     unsafe fn insert(&mut self, id: u32, value: C) {
-        self.channel.single_write(DetailedComponentEvent::Inserted(id));
+        self.inserted.add(id);
         self.storage.insert(id, value)
     }
 
     unsafe fn remove(&mut self, id: u32) -> C {
+        self.modified.remove(id);
         let removed = self.storage.remove(id);
-        self.channel.single_write(DetailedComponentEvent::Removed(id, removed.to_event()));
+        if !self.inserted.remove(id) {
+            // only report removals of components that readers were told about
+            if let Some(owner) = self.owners.get_mut(id as usize).and_then(Option::take) {
+                self.removed.push((owner, removed.to_event()));
+            }
+        }
         removed
     }
 }
@@ -90,6 +161,44 @@ impl<C, E, T> RemovalTracked<E> for RemovalFlaggedStorage<C, E, T> {
     fn removal_channel_mut(&mut self) -> &mut EventChannel<DetailedComponentEvent<E>> { &mut self.channel }
 }
 
+impl<C, E, T> MarkModified for RemovalFlaggedStorage<C, E, T>
+where
+    E: Event,
+{
+    fn mark_modified(&mut self, id: Index) { self.modified.add(id); }
+
+    fn flush_events(&mut self, entities: &EntitiesRes) {
+        // removals come first, since an id that was removed may have been reused by an insertion
+        let removed = self.removed.drain(..)
+            .map(|(entity, data)| DetailedComponentEvent::Removed(entity, data));
+        self.channel.iter_write(removed);
+
+        // Components can only be added to living entities, and are removed when their entity is
+        // deleted, so whichever entity has the id now is the one the component was inserted for.
+        for id in (&self.inserted).iter() {
+            let entity = entities.entity(id);
+            if self.owners.len() <= id as usize {
+                self.owners.resize(id as usize + 1, None);
+            }
+            self.owners[id as usize] = Some(entity);
+            self.channel.single_write(DetailedComponentEvent::Inserted(entity));
+        }
+
+        // newly-inserted components don't need a separate modification event
+        for id in (&self.modified).iter() {
+            if self.inserted.contains(id) {
+                continue;
+            }
+            if let Some(Some(entity)) = self.owners.get(id as usize) {
+                self.channel.single_write(DetailedComponentEvent::Modified(*entity));
+            }
+        }
+
+        self.inserted.clear();
+        self.modified.clear();
+    }
+}
+
 
 // -----------------------
 
@@ -101,13 +210,20 @@ pub trait RemovalBroadcaster<T> {
     fn register_detailed_reader(&mut self) -> ReaderId<DetailedComponentEvent<T>>;
 
     fn flag_detailed(&mut self, event: DetailedComponentEvent<T>);
+
+    /// Report that the given entity's component was changed.
+    fn mark_modified(&mut self, entity: Entity);
+
+    /// Write the buffered events for everything that happened since the last flush.
+    /// Readers should call this before reading the channel.
+    fn flush_events(&mut self, entities: &EntitiesRes);
 }
 
 
 impl<'e, C, E, D> RemovalBroadcaster<E> for Storage<'e, C, D>
 where
     C: Component + ToEvent<E>,
-    C::Storage: RemovalTracked<E>,
+    C::Storage: RemovalTracked<E> + MarkModified,
     E: Event,
     D: DerefMut<Target = MaskedStorage<C>>,
 {
@@ -126,5 +242,153 @@ where
     fn flag_detailed(&mut self, event: DetailedComponentEvent<E>) {
         self.detailed_channel_mut().single_write(event);
     }
+
+    fn mark_modified(&mut self, entity: Entity) {
+        if self.contains(entity) {
+            unsafe { self.open() }.1.mark_modified(entity.id());
+        }
+    }
+
+    fn flush_events(&mut self, entities: &EntitiesRes) {
+        unsafe { self.open() }.1.flush_events(entities);
+    }
+}
+
+// -----------------------
+
+/// Typed reader for a storage's `DetailedComponentEvent`s, which flushes the storage's
+/// buffered events before reading them.
+pub struct DetailedReader<E> {
+    reader_id: ReaderId<DetailedComponentEvent<E>>,
+}
+
+impl<E> DetailedReader<E>
+where
+    E: Event,
+{
+    pub fn new<S>(storage: &mut S) -> DetailedReader<E>
+    where
+        S: RemovalBroadcaster<E>,
+    {
+        DetailedReader {
+            reader_id: storage.register_detailed_reader(),
+        }
+    }
+
+    /// Every event since the last read.
+    pub fn read<'a, S>(&'a mut self, storage: &'a mut S, entities: &EntitiesRes) -> EventIterator<'a, DetailedComponentEvent<E>>
+    where
+        S: RemovalBroadcaster<E>,
+    {
+        storage.flush_events(entities);
+        storage.detailed_channel().read(&mut self.reader_id)
+    }
+
+    /// Every event since the last read, except `Inserted` and `Modified` events whose entity
+    /// has since been deleted. Those entities will have a `Removed` event of their own.
+    pub fn read_alive<'a, S>(
+        &'a mut self,
+        storage: &'a mut S,
+        entities: &'a EntitiesRes,
+    ) -> impl Iterator<Item = &'a DetailedComponentEvent<E>> + 'a
+    where
+        S: RemovalBroadcaster<E>,
+    {
+        self.read(storage, entities).filter(move |event| match event {
+            DetailedComponentEvent::Inserted(entity) | DetailedComponentEvent::Modified(entity) => entities.is_alive(*entity),
+            _ => true,
+        })
+    }
+}
+
+// -----------------------
+
+/// Components whose changes can be detected by the storage's `modify` guard, by taking a snapshot
+/// of the component before it's borrowed and comparing the component with it afterwards.
+pub trait DetectChanges {
+    type Snapshot;
+
+    fn snapshot(&self) -> Self::Snapshot;
+
+    fn changed_since(&self, snapshot: &Self::Snapshot) -> bool;
+}
+
+/// Mutable access to a component that reports a modification when dropped,
+/// but only if the component changed since the guard was created.
+pub struct ModifyGuard<'a, 'e, C, D>
+where
+    C: Component + DetectChanges,
+    C::Storage: MarkModified,
+    D: DerefMut<Target = MaskedStorage<C>>,
+{
+    storage: &'a mut Storage<'e, C, D>,
+    entity: Entity,
+    snapshot: C::Snapshot,
+}
+
+impl<'a, 'e, C, D> Deref for ModifyGuard<'a, 'e, C, D>
+where
+    C: Component + DetectChanges,
+    C::Storage: MarkModified,
+    D: DerefMut<Target = MaskedStorage<C>>,
+{
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        self.storage.get(self.entity).expect("Component was removed while being modified")
+    }
+}
+
+impl<'a, 'e, C, D> DerefMut for ModifyGuard<'a, 'e, C, D>
+where
+    C: Component + DetectChanges,
+    C::Storage: MarkModified,
+    D: DerefMut<Target = MaskedStorage<C>>,
+{
+    fn deref_mut(&mut self) -> &mut C {
+        self.storage.get_mut(self.entity).expect("Component was removed while being modified")
+    }
+}
+
+impl<'a, 'e, C, D> Drop for ModifyGuard<'a, 'e, C, D>
+where
+    C: Component + DetectChanges,
+    C::Storage: MarkModified,
+    D: DerefMut<Target = MaskedStorage<C>>,
+{
+    fn drop(&mut self) {
+        let changed = self.storage.get(self.entity).map_or(false, |c| c.changed_since(&self.snapshot));
+        if changed {
+            unsafe { self.storage.open() }.1.mark_modified(self.entity.id());
+        }
+    }
+}
+
+/// Extension for storages of `DetectChanges` components, to detect modifications automatically.
+pub trait ModifyTracked<'e, C, D>
+where
+    C: Component + DetectChanges,
+    C::Storage: MarkModified,
+    D: DerefMut<Target = MaskedStorage<C>>,
+{
+    /// Get mutable access to the entity's component. A modification is reported
+    /// when the returned guard is dropped, if the component was actually changed.
+    fn modify<'a>(&'a mut self, entity: Entity) -> Option<ModifyGuard<'a, 'e, C, D>>;
+}
+
+impl<'e, C, D> ModifyTracked<'e, C, D> for Storage<'e, C, D>
+where
+    C: Component + DetectChanges,
+    C::Storage: MarkModified,
+    D: DerefMut<Target = MaskedStorage<C>>,
+{
+    fn modify<'a>(&'a mut self, entity: Entity) -> Option<ModifyGuard<'a, 'e, C, D>> {
+        let snapshot = self.get(entity)?.snapshot();
+        Some(ModifyGuard {
+            storage: self,
+            entity,
+            snapshot,
+        })
+    }
 }
 
@@ -0,0 +1,193 @@
+use amethyst::ecs::prelude::*;
+
+use super::*;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Tracked(u32);
+
+impl Component for Tracked {
+    // removal events carry a snapshot of the whole component
+    type Storage = RemovalFlaggedStorage<Self>;
+}
+
+impl DetectChanges for Tracked {
+    type Snapshot = Tracked;
+
+    fn snapshot(&self) -> Tracked { self.clone() }
+
+    fn changed_since(&self, snapshot: &Tracked) -> bool { self != snapshot }
+}
+
+fn setup() -> (World, DetailedReader<Tracked>) {
+    let mut world = World::new();
+    world.register::<Tracked>();
+    let reader = DetailedReader::new(&mut world.write_storage::<Tracked>());
+    (world, reader)
+}
+
+fn read_events(world: &World, reader: &mut DetailedReader<Tracked>) -> Vec<DetailedComponentEvent<Tracked>> {
+    let mut storage = world.write_storage::<Tracked>();
+    let entities = world.entities();
+    reader.read(&mut storage, &entities).cloned().collect()
+}
+
+#[test]
+fn insert_emits_inserted_event_with_entity() {
+    let (mut world, mut reader) = setup();
+    let e = world.create_entity().with(Tracked(1)).build();
+
+    assert_eq!(read_events(&world, &mut reader), vec![DetailedComponentEvent::Inserted(e)]);
+    assert_eq!(read_events(&world, &mut reader), vec![]);
+}
+
+#[test]
+fn get_mut_alone_does_not_emit_modified() {
+    let (mut world, mut reader) = setup();
+    let e = world.create_entity().with(Tracked(1)).build();
+    read_events(&world, &mut reader);
+
+    world.write_storage::<Tracked>().get_mut(e).unwrap().0 = 2;
+    for _ in (&mut world.write_storage::<Tracked>()).join() {}
+
+    assert_eq!(read_events(&world, &mut reader), vec![]);
+}
+
+#[test]
+fn mark_modified_is_coalesced_per_flush() {
+    let (mut world, mut reader) = setup();
+    let e = world.create_entity().with(Tracked(1)).build();
+    read_events(&world, &mut reader);
+
+    {
+        let mut storage = world.write_storage::<Tracked>();
+        storage.mark_modified(e);
+        storage.mark_modified(e);
+        storage.mark_modified(e);
+    }
+
+    assert_eq!(read_events(&world, &mut reader), vec![DetailedComponentEvent::Modified(e)]);
+    assert_eq!(read_events(&world, &mut reader), vec![]);
+}
+
+#[test]
+fn modify_on_a_new_component_only_emits_inserted() {
+    let (mut world, mut reader) = setup();
+    let e = world.create_entity().with(Tracked(1)).build();
+
+    world.write_storage::<Tracked>().mark_modified(e);
+
+    assert_eq!(read_events(&world, &mut reader), vec![DetailedComponentEvent::Inserted(e)]);
+}
+
+#[test]
+fn modify_guard_only_reports_actual_changes() {
+    let (mut world, mut reader) = setup();
+    let e = world.create_entity().with(Tracked(1)).build();
+    read_events(&world, &mut reader);
+
+    {
+        let mut storage = world.write_storage::<Tracked>();
+        let mut guard = storage.modify(e).unwrap();
+        guard.0 = 1;
+    }
+    assert_eq!(read_events(&world, &mut reader), vec![]);
+
+    {
+        let mut storage = world.write_storage::<Tracked>();
+        let mut guard = storage.modify(e).unwrap();
+        guard.0 = 2;
+    }
+    assert_eq!(read_events(&world, &mut reader), vec![DetailedComponentEvent::Modified(e)]);
+    assert_eq!(world.read_storage::<Tracked>().get(e), Some(&Tracked(2)));
+}
+
+#[test]
+fn remove_emits_removed_event_with_snapshot() {
+    let (mut world, mut reader) = setup();
+    let e = world.create_entity().with(Tracked(1)).build();
+    read_events(&world, &mut reader);
+
+    world.write_storage::<Tracked>().mark_modified(e);
+    world.write_storage::<Tracked>().remove(e);
+
+    // the pending modification is dropped along with the component
+    assert_eq!(read_events(&world, &mut reader), vec![DetailedComponentEvent::Removed(e, Tracked(1))]);
+}
+
+#[test]
+fn insert_then_remove_between_flushes_is_not_reported() {
+    let (mut world, mut reader) = setup();
+    let e = world.create_entity().with(Tracked(1)).build();
+    world.write_storage::<Tracked>().remove(e);
+
+    assert_eq!(read_events(&world, &mut reader), vec![]);
+}
+
+#[test]
+fn entities_delete_emits_removed_on_maintain() {
+    let (mut world, mut reader) = setup();
+    let e = world.create_entity().with(Tracked(1)).build();
+    read_events(&world, &mut reader);
+
+    world.entities().delete(e).unwrap();
+    assert_eq!(read_events(&world, &mut reader), vec![], "components stay until the world is maintained");
+
+    world.maintain();
+    assert_eq!(read_events(&world, &mut reader), vec![DetailedComponentEvent::Removed(e, Tracked(1))]);
+}
+
+#[test]
+fn reused_index_is_reported_with_new_generation() {
+    let (mut world, mut reader) = setup();
+    let old = world.create_entity().with(Tracked(1)).build();
+    read_events(&world, &mut reader);
+
+    world.delete_entity(old).unwrap();
+    let new = world.create_entity().with(Tracked(2)).build();
+    assert_eq!(old.id(), new.id(), "expected the deleted entity's index to be reused");
+    assert_ne!(old, new);
+
+    assert_eq!(read_events(&world, &mut reader), vec![
+        DetailedComponentEvent::Removed(old, Tracked(1)),
+        DetailedComponentEvent::Inserted(new),
+    ]);
+}
+
+#[test]
+fn read_alive_skips_events_for_dead_entities() {
+    let (mut world, mut reader) = setup();
+    let mut other_reader = DetailedReader::new(&mut world.write_storage::<Tracked>());
+
+    // the other reader flushes the Inserted event into the channel...
+    let e = world.create_entity().with(Tracked(1)).build();
+    read_events(&world, &mut other_reader);
+
+    // ...but by the time this reader gets to it, the entity is gone
+    world.delete_entity(e).unwrap();
+
+    let mut storage = world.write_storage::<Tracked>();
+    let entities = world.entities();
+    let events: Vec<_> = reader.read_alive(&mut storage, &entities).cloned().collect();
+    assert_eq!(events, vec![DetailedComponentEvent::Removed(e, Tracked(1))]);
+}
+
+#[test]
+fn storage_clear_emits_cleared_event() {
+    let (mut world, mut reader) = setup();
+    let e1 = world.create_entity().with(Tracked(1)).build();
+    let e2 = world.create_entity().with(Tracked(2)).build();
+    read_events(&world, &mut reader);
+
+    world.write_storage::<Tracked>().remove(e2);
+    // never flushed, so nobody needs to hear about its removal
+    let e3 = world.create_entity().with(Tracked(3)).build();
+
+    world.write_storage::<Tracked>().clear();
+
+    assert_eq!(read_events(&world, &mut reader), vec![
+        DetailedComponentEvent::Removed(e2, Tracked(2)),
+        DetailedComponentEvent::Cleared(vec![(e1, Tracked(1))]),
+    ]);
+    assert_eq!(world.read_storage::<Tracked>().get(e3), None);
+    assert_eq!(read_events(&world, &mut reader), vec![]);
+}
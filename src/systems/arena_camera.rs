@@ -0,0 +1,156 @@
+use amethyst::{
+    core::{transform::Transform, Time},
+    ecs::prelude::{
+        Entities, Join, Read, ReadExpect, ReaderId, ReadStorage, Resources, System, SystemData, WriteStorage,
+    },
+    renderer::{Camera, Projection, ScreenDimensions},
+    shrev::EventChannel,
+};
+use rand::thread_rng;
+
+use crate::arena::{Arena, ArenaSpan, BarSide, LetterboxBar, ViewBounds};
+use crate::camera::CameraController;
+use crate::gameplay_log::GameplayEvent;
+
+/// The camera looks down on the arena from here.
+const CAMERA_Z: f32 = 1.0;
+/// In front of everything else in the arena, so the bars hide anything that strays outside of it.
+const BAR_Z: f32 = 0.8;
+
+/// System that fits the camera to the window, so the `Arena` is visible without being stretched,
+/// and moves the `LetterboxBar`s over the rest of the view.
+/// Cameras with a `CameraController` are also shaken (when the player runs into the wrong block),
+/// zoomed to the arena's `zoom`, and made to follow their target up and down tall arenas.
+/// It also keeps `ArenaSpan` entities stretched across the arena, in case it was resized.
+#[derive(Default)]
+pub struct ArenaCameraSystem {
+    event_reader: Option<ReaderId<GameplayEvent>>,
+}
+
+impl ArenaCameraSystem {
+    pub fn new() -> ArenaCameraSystem {
+        Default::default()
+    }
+}
+
+impl<'s> System<'s> for ArenaCameraSystem {
+    type SystemData = (
+        Entities<'s>,
+        Read<'s, Time>,
+        ReadExpect<'s, ScreenDimensions>,
+        Read<'s, Arena>,
+        Read<'s, EventChannel<GameplayEvent>>,
+        WriteStorage<'s, Camera>,
+        WriteStorage<'s, CameraController>,
+        ReadStorage<'s, LetterboxBar>,
+        ReadStorage<'s, ArenaSpan>,
+        WriteStorage<'s, Transform>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            time,
+            screen_dims,
+            arena,
+            events,
+            mut cameras,
+            mut controllers,
+            bars,
+            spans,
+            mut transforms,
+        ) = data;
+
+        let dt = time.delta_seconds();
+        let hits = events.read(self.event_reader.as_mut().unwrap())
+            .filter(|event| match event {
+                GameplayEvent::Hit { .. } => true,
+                _ => false,
+            })
+            .count();
+
+        // only tall arenas need following
+        let follows = arena.shown_height() < arena.height;
+        for controller in (&mut controllers).join() {
+            let target_height = controller.follow
+                .filter(|_| follows)
+                .and_then(|target| transforms.get(target))
+                .map(|transform| arena.follow_height(transform.translation().y));
+
+            controller.add_trauma(controller.hit_trauma * hits as f32);
+            controller.update(dt, arena.zoom, target_height);
+        }
+
+        let mut rng = thread_rng();
+        let mut shown = None;
+        for (entity, camera, transform) in (&*entities, &mut cameras, &mut transforms).join() {
+            let controller = controllers.get(entity);
+            let zoom = controller.map_or(arena.zoom, |controller| controller.zoom());
+            let center_y = controller
+                .and_then(|controller| controller.follow_height())
+                .unwrap_or(arena.height / 2.0);
+
+            let view = ViewBounds::fit(
+                (arena.width / 2.0, center_y),
+                (arena.width / zoom, arena.shown_height() / zoom),
+                screen_dims.width(),
+                screen_dims.height(),
+            );
+
+            // the projection is centered on the camera, so shaking rotates around the middle of the view
+            *camera = Camera::from(Projection::orthographic(
+                -view.width() / 2.0, view.width() / 2.0,
+                -view.height() / 2.0, view.height() / 2.0,
+            ));
+
+            let ((offset_x, offset_y), angle) = controller.map_or(((0.0, 0.0), 0.0), |controller| controller.shake(&mut rng));
+            let (x, y) = view.center();
+            transform.set_xyz(x + offset_x, y + offset_y, CAMERA_Z);
+            transform.set_rotation_euler(0.0, 0.0, angle);
+
+            shown = Some(view);
+        }
+
+        if let Some(view) = shown {
+            // the bars reach well past the edges of the view, so shaking doesn't uncover their ends
+            let margin = view.width().max(view.height());
+            let view = ViewBounds {
+                left: view.left - margin,
+                right: view.right + margin,
+                bottom: view.bottom - margin,
+                top: view.top + margin,
+            };
+            for (bar, transform) in (&bars, &mut transforms).join() {
+                // the corners of the area the bar covers. Bars on sides without any space are squashed flat.
+                let (left, right, bottom, top) = match bar.0 {
+                    BarSide::Left => (view.left, 0.0, view.bottom, view.top),
+                    BarSide::Right => (arena.width, view.right, view.bottom, view.top),
+                    BarSide::Bottom => (view.left, view.right, view.bottom, 0.0),
+                    BarSide::Top => (view.left, view.right, arena.height, view.top),
+                };
+                transform.set_xyz((left + right) / 2.0, (bottom + top) / 2.0, BAR_Z);
+                transform.set_scale((right - left).max(0.0), (top - bottom).max(0.0), 1.0);
+            }
+        }
+
+        for (span, transform) in (&spans, &mut transforms).join() {
+            match span {
+                ArenaSpan::Vertical => {
+                    transform.set_y(arena.height / 2.0);
+                    transform.set_scale(1.0, arena.height, 1.0);
+                },
+                ArenaSpan::Horizontal => {
+                    transform.set_x(arena.width / 2.0);
+                    transform.set_scale(arena.width, 1.0, 1.0);
+                },
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+
+        let mut events = res.fetch_mut::<EventChannel<GameplayEvent>>();
+        self.event_reader = Some(events.register_reader());
+    }
+}
@@ -0,0 +1,224 @@
+use std::f32::consts::PI;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use amethyst::{
+    core::nalgebra::{Isometry3, Point3, Vector3},
+    ecs::prelude::{Join, Read, ReadExpect, ReadStorage, Resources, System, SystemData, Write, WriteExpect},
+    input::InputHandler,
+    renderer::{DebugLines, DebugLinesParams, Rgba, VirtualKeyCode},
+};
+use ncollide3d::shape::{Ball, Cuboid, Shape};
+use serde::{Deserialize, Serialize};
+
+use crate::falldown::{Collider, EntityCollisionWorld};
+
+/// Everything is drawn at this depth, in front of the sprites but behind the camera.
+const DEBUG_Z: f32 = 0.5;
+const BALL_SEGMENTS: usize = 24;
+const CONTACT_SIZE: f32 = 2.0;
+const NORMAL_LENGTH: f32 = 8.0;
+const DIGIT_WIDTH: f32 = 3.0;
+const DIGIT_HEIGHT: f32 = 5.0;
+const DIGIT_SPACING: f32 = 1.5;
+
+/// Settings for the collision debug overlay. Usually loaded from `resources/collision_debug.ron`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CollisionDebugConfig {
+    pub enabled: bool,
+    pub draw_shapes: bool,
+    pub draw_aabbs: bool,
+    pub draw_contacts: bool,
+    pub draw_entity_ids: bool,
+    /// The key that toggles the overlay on and off.
+    pub toggle_key: VirtualKeyCode,
+}
+
+impl Default for CollisionDebugConfig {
+    fn default() -> Self {
+        CollisionDebugConfig {
+            enabled: false,
+            draw_shapes: true,
+            draw_aabbs: true,
+            draw_contacts: true,
+            draw_entity_ids: true,
+            toggle_key: VirtualKeyCode::F3,
+        }
+    }
+}
+
+// -------------------------------------------------------------------
+
+/// System that draws every collider's shape, bounding box and contact points
+/// (along with the id of its entity) using debug lines.
+/// The `A` and `B` are the type parameters of the `InputHandler`
+pub struct CollisionDebugSystem<A, B> {
+    toggle_was_down: bool,
+    _marker: PhantomData<(A, B)>,
+}
+
+impl<A, B> CollisionDebugSystem<A, B> {
+    pub fn new() -> CollisionDebugSystem<A, B> {
+        CollisionDebugSystem {
+            toggle_was_down: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'s, A, B> System<'s> for CollisionDebugSystem<A, B>
+    where
+        A: Hash + Eq + Clone + Send + Sync + 'static, // type constraints from InputHandler
+        B: Hash + Eq + Clone + Send + Sync + 'static, // type constraints from InputHandler
+{
+    type SystemData = (
+        Write<'s, CollisionDebugConfig>,
+        Read<'s, InputHandler<A, B>>,
+        ReadExpect<'s, EntityCollisionWorld>,
+        ReadStorage<'s, Collider>,
+        WriteExpect<'s, DebugLines>,
+    );
+
+    fn run(&mut self, (mut config, input, collision_world, colliders, mut lines): Self::SystemData) {
+        // toggle the overlay when the key is first pressed
+        let toggle_is_down = input.key_is_down(config.toggle_key);
+        if toggle_is_down && !self.toggle_was_down {
+            config.enabled = !config.enabled;
+        }
+        self.toggle_was_down = toggle_is_down;
+
+        if !config.enabled {
+            return;
+        }
+
+        let shape_color = Rgba(0.0, 0.8, 0.0, 1.0);
+        let aabb_color = Rgba(0.0, 0.4, 1.0, 1.0);
+        let contact_color = Rgba(1.0, 0.0, 0.0, 1.0);
+        let id_color = Rgba(0.2, 0.2, 0.2, 1.0);
+
+        for collider in (&colliders).join() {
+            let object = match collider.handle.and_then(|handle| collision_world.collision_object(handle)) {
+                Some(object) => object,
+                None => continue,
+            };
+            let position = object.position();
+            let aabb = object.shape().aabb(position);
+
+            if config.draw_shapes {
+                draw_shape(&mut lines, &**object.shape(), position, shape_color);
+            }
+            if config.draw_aabbs {
+                draw_rect(&mut lines, (aabb.mins().x, aabb.mins().y), (aabb.maxs().x, aabb.maxs().y), aabb_color);
+            }
+            if config.draw_entity_ids {
+                // just above the top-left corner of the bounding box
+                let origin = (aabb.mins().x, aabb.maxs().y + DIGIT_SPACING);
+                draw_number(&mut lines, object.data().id(), origin, id_color);
+            }
+        }
+
+        if config.draw_contacts {
+            for (_, _, _, manifold) in collision_world.contact_pairs(true) {
+                for tracked in manifold.contacts() {
+                    let contact = &tracked.contact;
+                    let (x, y) = (contact.world1.x, contact.world1.y);
+                    line(&mut lines, (x - CONTACT_SIZE, y - CONTACT_SIZE), (x + CONTACT_SIZE, y + CONTACT_SIZE), contact_color);
+                    line(&mut lines, (x - CONTACT_SIZE, y + CONTACT_SIZE), (x + CONTACT_SIZE, y - CONTACT_SIZE), contact_color);
+
+                    let normal: Vector3<f32> = contact.normal.into_inner() * NORMAL_LENGTH;
+                    line(&mut lines, (x, y), (x + normal.x, y + normal.y), contact_color);
+                }
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+
+        res.entry::<DebugLines>().or_insert_with(DebugLines::new);
+        res.entry::<DebugLinesParams>().or_insert_with(|| DebugLinesParams { line_width: 1.0 });
+    }
+}
+
+// -------------------------------------------------------------------
+
+fn line(lines: &mut DebugLines, (x1, y1): (f32, f32), (x2, y2): (f32, f32), color: Rgba) {
+    lines.draw_line(Point3::new(x1, y1, DEBUG_Z), Point3::new(x2, y2, DEBUG_Z), color);
+}
+
+fn draw_rect(lines: &mut DebugLines, (x1, y1): (f32, f32), (x2, y2): (f32, f32), color: Rgba) {
+    line(lines, (x1, y1), (x2, y1), color);
+    line(lines, (x2, y1), (x2, y2), color);
+    line(lines, (x2, y2), (x1, y2), color);
+    line(lines, (x1, y2), (x1, y1), color);
+}
+
+/// Draws the outline of balls and cuboids. Other shapes only get their bounding box drawn.
+fn draw_shape(lines: &mut DebugLines, shape: &dyn Shape<f32>, position: &Isometry3<f32>, color: Rgba) {
+    let to_world = |x: f32, y: f32| {
+        let p = position * Point3::new(x, y, 0.0);
+        (p.x, p.y)
+    };
+
+    if let Some(ball) = shape.as_shape::<Ball<f32>>() {
+        let radius = ball.radius();
+        let point_at = |i: usize| {
+            let angle = (i as f32) * 2.0 * PI / (BALL_SEGMENTS as f32);
+            to_world(radius * angle.cos(), radius * angle.sin())
+        };
+        for i in 0..BALL_SEGMENTS {
+            line(lines, point_at(i), point_at(i + 1), color);
+        }
+        // a spoke, so the ball's rotation is visible
+        line(lines, to_world(0.0, 0.0), point_at(0), color);
+    } else if let Some(cuboid) = shape.as_shape::<Cuboid<f32>>() {
+        let half = cuboid.half_extents();
+        let corners = [
+            to_world(-half.x, -half.y),
+            to_world(half.x, -half.y),
+            to_world(half.x, half.y),
+            to_world(-half.x, half.y),
+        ];
+        for (i, corner) in corners.iter().enumerate() {
+            line(lines, *corner, corners[(i + 1) % corners.len()], color);
+        }
+    }
+}
+
+/// Segments of a seven-segment display, as (start, end) points in a 1x2 cell:
+/// top, top-right, bottom-right, bottom, bottom-left, top-left, middle.
+const SEGMENTS: [((f32, f32), (f32, f32)); 7] = [
+    ((0.0, 2.0), (1.0, 2.0)),
+    ((1.0, 2.0), (1.0, 1.0)),
+    ((1.0, 1.0), (1.0, 0.0)),
+    ((0.0, 0.0), (1.0, 0.0)),
+    ((0.0, 0.0), (0.0, 1.0)),
+    ((0.0, 1.0), (0.0, 2.0)),
+    ((0.0, 1.0), (1.0, 1.0)),
+];
+
+/// Which of the `SEGMENTS` are lit for each digit, as a bitmask.
+const DIGIT_SEGMENTS: [u8; 10] = [
+    0b011_1111, 0b000_0110, 0b101_1011, 0b100_1111, 0b110_0110,
+    0b110_1101, 0b111_1101, 0b000_0111, 0b111_1111, 0b110_1111,
+];
+
+/// Draws `number` with its bottom-left corner at `origin`, seven-segment display style.
+fn draw_number(lines: &mut DebugLines, number: u32, (x, y): (f32, f32), color: Rgba) {
+    let digits = number.to_string();
+    let scale = (DIGIT_WIDTH, DIGIT_HEIGHT / 2.0);
+    for (i, digit) in digits.bytes().enumerate() {
+        let left = x + (i as f32) * (DIGIT_WIDTH + DIGIT_SPACING);
+        let mask = DIGIT_SEGMENTS[(digit - b'0') as usize];
+        for (segment, ((x1, y1), (x2, y2))) in SEGMENTS.iter().enumerate() {
+            if mask & (1 << segment) != 0 {
+                line(
+                    lines,
+                    (left + x1 * scale.0, y + y1 * scale.1),
+                    (left + x2 * scale.0, y + y2 * scale.1),
+                    color,
+                );
+            }
+        }
+    }
+}
@@ -1,13 +1,17 @@
-use crate::falldown::FallingObject;
+use crate::falldown::{Affiliation, FallingObject, Hit};
+use crate::gameplay_log::GameplayEvent;
+use crate::particles::ParticleEmitter;
+use crate::timing::FixedTimestep;
 
 use amethyst::{
     core::{
         nalgebra::Vector3,
-        timing::Time,
         transform::Transform,
     },
 
-    ecs::prelude::{Entities, Join, Read, ReadStorage, WriteStorage, System},
+    ecs::prelude::{Entities, Join, Read, ReadStorage, Write, WriteStorage, System},
+    renderer::Rgba,
+    shrev::EventChannel,
 };
 
 pub struct FallingObjectSystem;
@@ -17,24 +21,52 @@ impl<'s> System<'s> for FallingObjectSystem {
         Entities<'s>,
         ReadStorage<'s, FallingObject>,
         WriteStorage<'s, Transform>,
-        Read<'s, Time>,
+        Read<'s, FixedTimestep>,
+        ReadStorage<'s, Affiliation>,
+        ReadStorage<'s, Hit>,
+        Write<'s, EventChannel<GameplayEvent>>,
+        WriteStorage<'s, ParticleEmitter>,
     );
 
-    fn run(&mut self, (entities, falling_objects, mut transforms, time): Self::SystemData) {
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, falling_objects, mut transforms, timestep, affiliations, hits, mut gameplay_events, mut emitters) = data;
+        let dt = timestep.step_seconds();
+
         for (e, o, t) in (&*entities, &falling_objects, &mut transforms).join() {
             // alias to help intelliJ out
             let obj: &FallingObject = o;
             let transform: &mut Transform = t;
 
-            // move the object downward
-            transform.translate_y(-obj.fall_rate * time.delta_seconds());
-            if transform.translation().y < -obj.radius {
-                // delete objects that reach the bottom of the screen
-                entities.delete(e).unwrap();
-            }
+            for _ in 0..timestep.steps() {
+                // move the object downward
+                transform.translate_y(-obj.fall_rate * dt);
+
+                // spin the object
+                transform.rotate_global(Vector3::z_axis(), obj.spin_rate * dt);
+
+                if transform.translation().y < -obj.radius {
+                    // delete objects that reach the bottom of the screen
+                    entities.delete(e).unwrap();
 
-            // spin the object
-            transform.rotate_global(Vector3::z_axis(), obj.spin_rate * time.delta_seconds());
+                    // the player already ran into this one, so it wasn't missed
+                    if hits.contains(e) {
+                        break;
+                    }
+
+                    let color = match affiliations.get(e) {
+                        Some(Affiliation::Enemy(color)) => Some(color.clone()),
+                        _ => None,
+                    };
+
+                    // a puff where the block left the bottom of the screen
+                    let position = Vector3::new(transform.translation().x, 0.0, 0.0);
+                    let tint = color.as_ref().map_or(Rgba(1.0, 1.0, 1.0, 1.0), |color| color.rgba());
+                    entities.build_entity().with(ParticleEmitter::miss(position, tint), &mut emitters).build();
+
+                    gameplay_events.single_write(GameplayEvent::Missed { block: e.into(), color });
+                    break;
+                }
+            }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,21 @@
+use amethyst::{
+    core::timing::Time,
+    ecs::prelude::{Read, System, Write},
+};
+
+use crate::timing::FixedTimestep;
+
+/// System that feeds the frame's delta time into the `FixedTimestep` resource.
+/// Gameplay systems that need to be frame-rate independent should depend on this one.
+pub struct FixedTimestepSystem;
+
+impl<'s> System<'s> for FixedTimestepSystem {
+    type SystemData = (
+        Read<'s, Time>,
+        Write<'s, FixedTimestep>,
+    );
+
+    fn run(&mut self, (time, mut timestep): Self::SystemData) {
+        timestep.advance(time.delta_seconds());
+    }
+}
@@ -0,0 +1,42 @@
+use amethyst::{
+    core::Time,
+    ecs::prelude::{Read, ReaderId, Resources, System, SystemData, Write},
+    shrev::EventChannel,
+};
+
+use crate::gameplay_log::{GameplayEvent, GameplayLog};
+
+/// System that writes every `GameplayEvent` published this frame to the `GameplayLog`.
+#[derive(Default)]
+pub struct GameplayLogSystem {
+    event_reader: Option<ReaderId<GameplayEvent>>,
+}
+
+impl GameplayLogSystem {
+    pub fn new() -> GameplayLogSystem {
+        Default::default()
+    }
+}
+
+impl<'s> System<'s> for GameplayLogSystem {
+    type SystemData = (
+        Read<'s, Time>,
+        Read<'s, EventChannel<GameplayEvent>>,
+        Write<'s, GameplayLog>,
+    );
+
+    fn run(&mut self, (time, events, mut log): Self::SystemData) {
+        for event in events.read(self.event_reader.as_mut().unwrap()) {
+            log.record(time.absolute_time_seconds(), time.frame_number(), event);
+        }
+        // flush every frame, so a crash loses as little of the run as possible
+        log.flush();
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+
+        let mut events = res.fetch_mut::<EventChannel<GameplayEvent>>();
+        self.event_reader = Some(events.register_reader());
+    }
+}
@@ -1,13 +1,31 @@
+mod arena_camera;
+mod collision_debug;
 mod falling_object;
+mod fixed_timestep;
+mod gameplay_log;
+mod mouse_world_position;
 mod object_collection;
+mod particles;
 mod player_movement;
+mod sound_effects;
 mod spawner;
+mod sprite_animation;
 mod sync_collision_world;
+mod sync_external_world;
 
 pub use self::{
+    arena_camera::ArenaCameraSystem,
+    collision_debug::{CollisionDebugConfig, CollisionDebugSystem},
     falling_object::FallingObjectSystem,
+    fixed_timestep::FixedTimestepSystem,
+    gameplay_log::GameplayLogSystem,
+    mouse_world_position::MouseWorldPositionSystem,
     object_collection::*,
+    particles::ParticleSystem,
     player_movement::*,
+    sound_effects::SoundEffectSystem,
     spawner::SpawnerSystem,
+    sprite_animation::SpriteAnimationSystem,
     sync_collision_world::*,
+    sync_external_world::SyncExternalWorld,
 };
\ No newline at end of file
@@ -0,0 +1,59 @@
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use amethyst::{
+    core::transform::GlobalTransform,
+    ecs::prelude::{Join, Read, ReadExpect, ReadStorage, System, Write},
+    input::InputHandler,
+    renderer::{ActiveCamera, Camera, ScreenDimensions},
+};
+
+use crate::mouse::{screen_to_world, MouseWorldPosition};
+
+/// System that keeps the `MouseWorldPosition` up to date, using the active camera
+/// (or any camera, if none is active).
+/// The camera's transform is the one from the end of the previous frame.
+/// The `A` and `B` are the type parameters of the `InputHandler`
+pub struct MouseWorldPositionSystem<A, B> {
+    _marker: PhantomData<(A, B)>,
+}
+
+impl<A, B> MouseWorldPositionSystem<A, B> {
+    pub fn new() -> MouseWorldPositionSystem<A, B> {
+        MouseWorldPositionSystem {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'s, A, B> System<'s> for MouseWorldPositionSystem<A, B>
+    where
+        A: Hash + Eq + Clone + Send + Sync + 'static, // type constraints from InputHandler
+        B: Hash + Eq + Clone + Send + Sync + 'static, // type constraints from InputHandler
+{
+    type SystemData = (
+        Read<'s, InputHandler<A, B>>,
+        ReadExpect<'s, ScreenDimensions>,
+        Option<Read<'s, ActiveCamera>>,
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, GlobalTransform>,
+        Write<'s, MouseWorldPosition>,
+    );
+
+    fn run(&mut self, (input, screen_dims, active_camera, cameras, transforms, mut mouse): Self::SystemData) {
+        let camera = active_camera
+            .and_then(|active| Some((cameras.get(active.entity)?, transforms.get(active.entity)?)))
+            .or_else(|| (&cameras, &transforms).join().next());
+
+        mouse.position = match (input.mouse_position(), camera) {
+            (Some((pixel_x, pixel_y)), Some((camera, camera_transform))) => screen_to_world(
+                camera,
+                camera_transform,
+                (pixel_x as f32, pixel_y as f32),
+                screen_dims.width(),
+                screen_dims.height(),
+            ),
+            _ => None,
+        };
+    }
+}
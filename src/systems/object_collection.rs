@@ -1,27 +1,51 @@
 use amethyst::{
+    core::transform::{ParentHierarchy, Transform},
     ecs::prelude::*,
     shrev::EventChannel,
 };
-use crate::falldown::{Affiliation, CollectionEvent, EntityContactEvent};
+use log::{info, trace};
+use crate::animation::{Animations, SpriteAnimation};
+use crate::collision_layers::CollisionLayers;
+use crate::falldown::{Affiliation, Collider, CollectionEvent, CollisionEventKind, EntityCollisionEvent, Hit};
+use crate::gameplay_log::GameplayEvent;
+use crate::particles::ParticleEmitter;
+use crate::storage::ModifyTracked;
 use std::convert::Into;
 
+const LOG_TARGET: &str = "falldown::collection";
+
+/// How many blocks the player has to catch in a row, without running into one, to power up.
+const POWER_UP_STREAK: u32 = 10;
+
 pub struct ObjectCollection {
-    contact_reader: Option<ReaderId<EntityContactEvent>>,
+    contact_reader: Option<ReaderId<EntityCollisionEvent>>,
+    /// Blocks caught since the player last ran into one (or powered up).
+    streak: u32,
 }
 
 impl ObjectCollection {
     pub fn new() -> ObjectCollection {
         ObjectCollection {
             contact_reader: None,
+            streak: 0,
         }
     }
 }
 
 impl<'s> System<'s> for ObjectCollection {
     type SystemData = (
-        Read<'s, EventChannel<EntityContactEvent>>,
+        Read<'s, EventChannel<EntityCollisionEvent>>,
         ReadStorage<'s, Affiliation>,
-        Entities<'s>
+        Entities<'s>,
+        Write<'s, EventChannel<GameplayEvent>>,
+        ReadExpect<'s, ParentHierarchy>,
+        Read<'s, Animations>,
+        WriteStorage<'s, SpriteAnimation>,
+        ReadStorage<'s, Transform>,
+        WriteStorage<'s, ParticleEmitter>,
+        WriteStorage<'s, Collider>,
+        ReadExpect<'s, CollisionLayers>,
+        WriteStorage<'s, Hit>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -29,23 +53,85 @@ impl<'s> System<'s> for ObjectCollection {
             contact_events,
             affiliations,
             entities,
+            mut gameplay_events,
+            hierarchy,
+            animations,
+            mut sprite_animations,
+            transforms,
+            mut emitters,
+            mut colliders,
+            collision_layers,
+            mut hits,
         ) = data;
 
-        for (entity1, entity2, _contact) in contact_events.read(self.contact_reader.as_mut().unwrap()) {
+        for event in contact_events.read(self.contact_reader.as_mut().unwrap()) {
+            // blocks are only collected when the player first touches them
+            let (entity1, entity2) = match event.kind {
+                CollisionEventKind::ContactStarted(_) => (&event.entity1, &event.entity2),
+                _ => continue,
+            };
+
             let affiliation1 = affiliations.get(*entity1);
             let affiliation2 = affiliations.get(*entity2);
 
             match ((entity1, affiliation1, entity2, affiliation2)).into() {
-                CollectionEvent::CaughtBlock { block, color, is_correct, .. } => {
+                CollectionEvent::CaughtBlock { player, block, color, is_correct } => {
+                    let color = color.clone();
+
+                    // the player's sprite is on one of its children
+                    let animation = animations.get(if is_correct { "player_catch" } else { "player_hit" });
+                    if let Some(animation) = animation {
+                        for child in hierarchy.children(*player) {
+                            if let Some(sprite_animation) = sprite_animations.get_mut(*child) {
+                                sprite_animation.play(animation.clone());
+                            }
+                        }
+                    }
+
+                    if !is_correct {
+                        if let (Some(animation), Some(sprite_animation)) = (animations.get("block_shatter"), sprite_animations.get_mut(*block)) {
+                            sprite_animation.play(animation);
+                        }
+                    }
+
+                    if let Some(transform) = transforms.get(*block) {
+                        let position = *transform.translation();
+                        let emitter = if is_correct {
+                            ParticleEmitter::catch(position, color.rgba())
+                        } else {
+                            ParticleEmitter::hit(position, color.rgba())
+                        };
+                        entities.build_entity().with(emitter, &mut emitters).build();
+                    }
+
                     if is_correct {
-                        println!("Player caught {:?} block {}", color, block.id());
+                        info!(target: LOG_TARGET, "Player caught {:?} block {}", color, block.id());
                         entities.delete(*block).unwrap();
+                        gameplay_events.single_write(GameplayEvent::Caught { block: (*block).into(), color });
+
+                        self.streak += 1;
+                        if self.streak == POWER_UP_STREAK {
+                            info!(target: LOG_TARGET, "Player powered up after catching {} blocks in a row", self.streak);
+                            gameplay_events.single_write(GameplayEvent::PoweredUp { streak: self.streak });
+                            self.streak = 0;
+                        }
                     } else {
-                        println!("Player ran into {:?} block {}", color, block.id());
+                        info!(target: LOG_TARGET, "Player ran into {:?} block {}", color, block.id());
+                        gameplay_events.single_write(GameplayEvent::Hit { block: (*block).into(), color });
+                        self.streak = 0;
+                        // the block might have been deleted already, e.g. by falling out of the arena
+                        hits.insert(*block, Hit).ok();
+
+                        // the block passes through the player from now on, instead of hitting it again
+                        if let (Some(mut collider), Ok(player_layer)) = (colliders.modify(*block), collision_layers.layer("player")) {
+                            let mut groups = collider.groups().clone();
+                            groups.modify_whitelist(player_layer, false);
+                            collider.set_groups(groups);
+                        }
                     }
                 },
                 CollectionEvent::Unknown => {
-                    println!("Some other collision happened between {:?} and {:?}", entity1, entity2);
+                    trace!(target: LOG_TARGET, "Some other collision happened between {:?} and {:?}", entity1, entity2);
                 },
             }
         }
@@ -54,7 +140,7 @@ impl<'s> System<'s> for ObjectCollection {
     fn setup(&mut self, res: &mut Resources) {
         Self::SystemData::setup(res);
 
-        let mut contact_events = res.fetch_mut::<EventChannel<EntityContactEvent>>();
+        let mut contact_events = res.fetch_mut::<EventChannel<EntityCollisionEvent>>();
         self.contact_reader = Some(contact_events.register_reader());
     }
 }
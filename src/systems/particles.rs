@@ -0,0 +1,142 @@
+use amethyst::{
+    core::{nalgebra::Vector3, transform::Transform, Time},
+    ecs::prelude::{Entities, Entity, Join, Read, ReadStorage, System, WriteStorage},
+    renderer::{Hidden, Rgba, SpriteRender, Transparent},
+};
+use rand::{Rng, thread_rng};
+
+use crate::assets::Sprites;
+use crate::particles::{Particle, ParticleEmitter};
+
+/// Particles lose this fraction of their speed every second.
+const DRAG: f32 = 3.0;
+/// In front of the blocks, but behind the cursor lines.
+const PARTICLE_Z: f32 = 0.05;
+
+/// System that turns `ParticleEmitter`s into particles, and moves and fades the particles.
+/// Particles that have run their course are hidden and kept around to be used again.
+#[derive(Default)]
+pub struct ParticleSystem {
+    free: Vec<Entity>,
+    expired: Vec<Entity>,
+}
+
+impl ParticleSystem {
+    pub fn new() -> ParticleSystem {
+        Default::default()
+    }
+}
+
+impl<'s> System<'s> for ParticleSystem {
+    type SystemData = (
+        Entities<'s>,
+        Read<'s, Time>,
+        Read<'s, Sprites>,
+        ReadStorage<'s, ParticleEmitter>,
+        WriteStorage<'s, Particle>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Rgba>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transparent>,
+        WriteStorage<'s, Hidden>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            time,
+            sprites,
+            emitters,
+            mut particles,
+            mut transforms,
+            mut colors,
+            mut sprite_renders,
+            mut transparents,
+            mut hidden,
+        ) = data;
+
+        let dt = time.delta_seconds();
+
+        // move and fade the particles that are in use
+        for (entity, particle, transform, color, _) in (&*entities, &mut particles, &mut transforms, &mut colors, !&hidden).join() {
+            let particle: &mut Particle = particle;
+            let transform: &mut Transform = transform;
+
+            particle.age += dt;
+            if !particle.is_alive() {
+                self.expired.push(entity);
+                continue;
+            }
+
+            transform.translate_x(particle.velocity.x * dt);
+            transform.translate_y(particle.velocity.y * dt);
+            particle.velocity *= (1.0 - DRAG * dt).max(0.0);
+
+            let remaining = 1.0 - particle.progress();
+            let size = particle.size * remaining;
+            transform.set_scale(size, size, 1.0);
+
+            let Rgba(r, g, b, a) = particle.color;
+            *color = Rgba(r, g, b, a * remaining);
+        }
+
+        for entity in self.expired.drain(..) {
+            hidden.insert(entity, Hidden).unwrap();
+            self.free.push(entity);
+        }
+
+        // emit the new bursts
+        let mut rng = thread_rng();
+        for (emitter_entity, emitter) in (&*entities, &emitters).join() {
+            let emitter: &ParticleEmitter = emitter;
+
+            for _ in 0..emitter.count {
+                // reuse a hidden particle if there is one. Pooled particles may have been
+                // deleted along with everything else, e.g. when the state changed.
+                let reused = loop {
+                    match self.free.pop() {
+                        Some(entity) if entities.is_alive(entity) => break Some(entity),
+                        Some(_) => continue,
+                        None => break None,
+                    }
+                };
+                let entity = match reused {
+                    Some(entity) => {
+                        hidden.remove(entity);
+                        entity
+                    },
+                    None => {
+                        let entity = entities.create();
+                        sprite_renders.insert(entity, sprites.sprite("pixel")).unwrap();
+                        transparents.insert(entity, Transparent).unwrap();
+                        entity
+                    },
+                };
+
+                let angle = emitter.direction + random_in(&mut rng, (-emitter.spread, emitter.spread));
+                let speed = random_in(&mut rng, emitter.speed);
+
+                let mut transform = Transform::default();
+                transform.set_xyz(emitter.position.x, emitter.position.y, PARTICLE_Z);
+                transform.set_scale(emitter.size, emitter.size, 1.0);
+
+                transforms.insert(entity, transform).unwrap();
+                colors.insert(entity, emitter.color).unwrap();
+                particles.insert(entity, Particle {
+                    velocity: Vector3::new(angle.cos(), angle.sin(), 0.0) * speed,
+                    age: 0.0,
+                    lifetime: random_in(&mut rng, emitter.lifetime),
+                    size: emitter.size,
+                    color: emitter.color,
+                }).unwrap();
+            }
+
+            entities.delete(emitter_entity).unwrap();
+        }
+    }
+}
+
+/// A random value in the range, which may be empty.
+fn random_in<R: Rng>(rng: &mut R, (low, high): (f32, f32)) -> f32 {
+    if high > low { rng.gen_range(low, high) } else { low }
+}
@@ -5,105 +5,89 @@ use amethyst::{
     controls::{CursorHideSystem, MouseFocusUpdateSystem},
     core::{
         SystemBundle,
+        timing::Time,
         transform::Transform,
     },
     ecs::prelude::{
-        DispatcherBuilder, Join, Read, ReadExpect, ReadStorage, System, WriteStorage,
+        DispatcherBuilder, Join, Read, ReadStorage, System, WriteStorage,
     },
     Error,
-    input::InputHandler,
-    renderer::ScreenDimensions,
 };
 
-use crate::falldown::{ARENA_HEIGHT, ARENA_WIDTH, FollowMouse, Player};
+use crate::arena::Arena;
+use crate::falldown::{FollowMouse, Player};
+use crate::mouse::MouseWorldPosition;
+use crate::systems::MouseWorldPositionSystem;
+use crate::timing::FixedTimestep;
 
 const PI_OVER_180: f32 = std::f32::consts::PI / 180.0;
 
 // -------------------------------------------------------------------
 
-/// System that updates the Transform of any entity with the `FollowMouse` component.
-/// The `A` and `B` are the type parameters of the `InputHandler`
-pub struct FollowMouseSystem<A, B> {
-    _marker: PhantomData<(A, B)>
-}
+/// System that moves any entity with the `FollowMouse` component towards the mouse.
+/// Followers `in_player_zone` (like the `Player`) are kept to the `Arena`'s current player zone.
+pub struct FollowMouseSystem;
 
-impl<A, B> FollowMouseSystem<A, B> {
-    pub fn new() -> FollowMouseSystem<A, B> {
-        FollowMouseSystem {
-            _marker: PhantomData,
-        }
-    }
-}
-
-impl<'s, A, B> System<'s> for FollowMouseSystem<A, B>
-    where
-        A: Hash + Eq + Clone + Send + Sync + 'static, // type constraints from InputHandler
-        B: Hash + Eq + Clone + Send + Sync + 'static, // type constraints from InputHandler
-{
+impl<'s> System<'s> for FollowMouseSystem {
     type SystemData = (
         ReadStorage<'s, FollowMouse>,
         WriteStorage<'s, Transform>,
-        Read<'s, InputHandler<A, B>>,
-        ReadExpect<'s, ScreenDimensions>,
+        Read<'s, MouseWorldPosition>,
+        Read<'s, Arena>,
+        Read<'s, Time>,
     );
 
-    fn run(&mut self, (followers, mut transforms, input, screen_dims): Self::SystemData) {
+    fn run(&mut self, (followers, mut transforms, mouse, arena, time): Self::SystemData) {
+        // get the mouse's position within the arena, keeping followers inside it
+        // when the mouse is over the bars around it
+        let mouse_arena_position = mouse.position.map(|position| arena.clamp((position.x, position.y)));
 
-        // get an (x, y) tuple of values in the range [0.0, 1.0) representing the mouse's position on screen
-        let mouse_logical_position = input.mouse_position().map(|(pixel_x, pixel_y)| {
-            (
-                (pixel_x as f32) / screen_dims.width(),
-                (screen_dims.height() - (pixel_y as f32)) / screen_dims.height()
-            )
-        });
+        for (follower, transform) in (&followers, &mut transforms).join() {
+            let follower = if follower.in_player_zone { follower.in_zone(&arena.player_zone) } else { *follower };
 
-        if let Some((mouse_x, mouse_y)) = mouse_logical_position {
-            let target_x = mouse_x * ARENA_WIDTH;
-            let target_y = mouse_y * ARENA_HEIGHT;
-
-            // move all `FollowMouse` entities towards the mouse's position within the Arena
-            for (follower, transform) in (&followers, &mut transforms).join() {
-                transform.translate_x((target_x - transform.translation().x) * follower.x_ratio);
-                transform.translate_y((target_y - transform.translation().y) * follower.y_ratio);
+            let mut position = (transform.translation().x, transform.translation().y);
+            if let Some(target) = mouse_arena_position {
+                // move towards the mouse's position within the Arena
+                position = follower.step(position, target, time.delta_seconds());
             }
+            // keep it in bounds even when it isn't moving, e.g. after the arena shrinks
+            let (x, y) = follower.bounds.clamp(position, &arena);
+            transform.set_x(x);
+            transform.set_y(y);
         }
     }
 }
 
 // -------------------------------------------------------------------
 
+/// System that leans the `Player` into its horizontal movement.
+/// The lean is based on the player's velocity over a fixed window of time
+/// and smoothed with a spring, so it feels the same at any frame rate.
 pub struct PlayerRotateSystem;
 
 impl<'s> System<'s> for PlayerRotateSystem {
     type SystemData = (
         WriteStorage<'s, Player>,
         WriteStorage<'s, Transform>,
+        Read<'s, Time>,
+        Read<'s, FixedTimestep>,
     );
 
-    fn run(&mut self, (mut players, mut transforms): Self::SystemData) {
+    fn run(&mut self, (mut players, mut transforms, time, timestep): Self::SystemData) {
         for (p, t) in (&mut players, &mut transforms).join() {
             let player: &mut Player = p;
             let transform: &mut Transform = t;
 
             // update the "trail" with the current position
-            player.trail.push(*transform.translation());
-
-            // see how far the player has moved in the last few frames,
-            // and decide an angle for it to face (more travel = more angle)
-            let x_movement = {
-                match player.trail.oldest() {
-                    Some(v) => transform.translation().x - v.x,
-                    None => 0f32
-                }
-            };
-            let angle = {
-                if x_movement.abs() > 0.1 {
-                    x_movement * -2.0
-                } else {
-                    0f32
-                }
-            }.min(45.0).max(-45.0);
-            transform.set_rotation_euler(0.0, 0.0, angle * PI_OVER_180);
+            player.trail.push(time.absolute_time_seconds(), *transform.translation());
+
+            // see how fast the player has been moving recently,
+            // and decide an angle for it to face (more speed = more angle)
+            let target_angle = player.tilt.target_angle(player.trail.velocity().x);
+            for _ in 0..timestep.steps() {
+                player.tilt.step(target_angle, timestep.step_seconds());
+            }
+            transform.set_rotation_euler(0.0, 0.0, player.tilt.angle() * PI_OVER_180);
         }
     }
 }
@@ -128,7 +112,8 @@ impl<'a, 'b, A, B> SystemBundle<'a, 'b> for PlayerControlBundle<A, B>
         B: Hash + Eq + Clone + Send + Sync + 'static, // type constraints from InputHandler
 {
     fn build(self, builder: &mut DispatcherBuilder) -> Result<(), Error> {
-        builder.add(FollowMouseSystem::<A, B>::new(), "follow_mouse", &[]);
+        builder.add(MouseWorldPositionSystem::<A, B>::new(), "mouse_world_position", &[]);
+        builder.add(FollowMouseSystem, "follow_mouse", &["mouse_world_position"]);
         builder.add(PlayerRotateSystem, "player_rotate", &["follow_mouse"]);
         builder.add(MouseFocusUpdateSystem::new(), "mouse_focus", &[]);
         builder.add(CursorHideSystem::new(), "cursor_hide", &["mouse_focus"]);
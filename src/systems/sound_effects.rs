@@ -0,0 +1,56 @@
+use amethyst::{
+    assets::AssetStorage,
+    audio::{output::Output, Source},
+    ecs::prelude::{Read, ReaderId, Resources, System, SystemData},
+    shrev::EventChannel,
+};
+
+use crate::audio::{AudioConfig, Sounds};
+use crate::gameplay_log::GameplayEvent;
+
+/// System that plays a sound effect for each gameplay event that has one.
+/// Without an audio device (or with audio disabled) it does nothing.
+#[derive(Default)]
+pub struct SoundEffectSystem {
+    event_reader: Option<ReaderId<GameplayEvent>>,
+}
+
+impl SoundEffectSystem {
+    pub fn new() -> SoundEffectSystem {
+        Default::default()
+    }
+}
+
+impl<'s> System<'s> for SoundEffectSystem {
+    type SystemData = (
+        Read<'s, EventChannel<GameplayEvent>>,
+        Read<'s, Sounds>,
+        Read<'s, AssetStorage<Source>>,
+        Option<Read<'s, Output>>,
+        Read<'s, AudioConfig>,
+    );
+
+    fn run(&mut self, (events, sounds, storage, output, config): Self::SystemData) {
+        for event in events.read(self.event_reader.as_mut().unwrap()) {
+            // the names of the sounds in the asset manifest
+            let sound = match event {
+                GameplayEvent::Caught { .. } => "catch",
+                GameplayEvent::Hit { .. } => "hit",
+                GameplayEvent::Missed { .. } => "miss",
+                GameplayEvent::WaveStarted { .. } => "wave_start",
+                GameplayEvent::PoweredUp { .. } => "power_up",
+                _ => continue,
+            };
+            if config.enabled {
+                sounds.play(sound, config.effects_volume, &storage, output.as_ref().map(|output| &**output));
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+
+        let mut events = res.fetch_mut::<EventChannel<GameplayEvent>>();
+        self.event_reader = Some(events.register_reader());
+    }
+}
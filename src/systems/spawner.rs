@@ -1,20 +1,23 @@
 use std::f32::consts::PI;
+use std::sync::Arc;
 
 use amethyst::{
-    core::{
-        timing::Time,
-        transform::Transform,
-    },
-    ecs::prelude::{Entities, Join, Read, System, WriteStorage},
+    core::transform::Transform,
+    ecs::prelude::{Entities, Join, Read, ReadExpect, System, Write, WriteStorage},
     renderer::{Rgba, SpriteRender},
+    shrev::EventChannel,
 };
 use ncollide3d::shape::Ball;
 use ncollide3d::shape::ShapeHandle;
 use ncollide3d::world::GeometricQueryType;
 use rand::{Rng, thread_rng};
 
-use crate::falldown::{Affiliation, ARENA_HEIGHT, ARENA_WIDTH, Collider, ColorType, FallingObject, Spawner};
-use crate::falldown::enemy_collision_group;
+use crate::animation::{Animation, Animations, SpriteAnimation};
+use crate::arena::Arena;
+use crate::falldown::{Affiliation, Collider, ColorType, FallingObject, Spawner};
+use crate::collision_layers::CollisionLayers;
+use crate::gameplay_log::GameplayEvent;
+use crate::timing::FixedTimestep;
 use crate::util::RngExtras;
 
 pub struct SpawnerSystem;
@@ -24,7 +27,11 @@ const SPAWNED_OBJECT_RADIUS: f32 = 5.0;
 impl<'s> System<'s> for SpawnerSystem {
     type SystemData = (
         WriteStorage<'s, Spawner>,
-        Read<'s, Time>,
+        Read<'s, FixedTimestep>,
+        ReadExpect<'s, CollisionLayers>,
+        Read<'s, Arena>,
+        Write<'s, EventChannel<GameplayEvent>>,
+        Read<'s, Animations>,
         // extra fields required in order to spawn entities with those fields
         Entities<'s>,
         WriteStorage<'s, Collider>,
@@ -33,64 +40,88 @@ impl<'s> System<'s> for SpawnerSystem {
         WriteStorage<'s, FallingObject>,
         WriteStorage<'s, SpriteRender>,
         WriteStorage<'s, Rgba>,
+        WriteStorage<'s, SpriteAnimation>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             mut spawners,
-            time,
+            timestep,
+            collision_layers,
+            arena,
+            mut gameplay_events,
+            animations,
             entities,
             mut collision_objects,
             mut affiliations,
             mut transforms,
             mut falling_objects,
             mut sprites,
-            mut colors
+            mut colors,
+            mut sprite_animations,
         ) = data;
 
         for (s,) in (&mut spawners,).join() {
             let spawner: &mut Spawner = s;
-
-            let should_spawn = spawner.advance_and_reset(time.delta_seconds()) && spawner.remaining > 0;
-            if should_spawn {
-                spawner.remaining -= 1;
-                let mut rng = thread_rng();
-
-                // pick a random starting position along the top of the screen
-                let mut transform = Transform::default();
-                transform.set_xyz(
-                    rng.gen_range(0.0, ARENA_WIDTH),
-                    ARENA_HEIGHT + SPAWNED_OBJECT_RADIUS,
-                    0.0,
-                );
-
-                // randomize the falling object's speed and spin
-                let object = FallingObject {
-                    fall_rate: rng.gen_range(60.0, 120.0),
-                    spin_rate: rng.gen_range(0.25, 1.5) * PI * rng.plus_or_minus(1.0),
-                    radius: SPAWNED_OBJECT_RADIUS,
-                };
-
-                let color = rng.gen::<ColorType>();
-
-                let collider = Collider::new(
-                    ShapeHandle::new(Ball::new(SPAWNED_OBJECT_RADIUS)),
-                    enemy_collision_group(),
-                    GeometricQueryType::Contacts(0f32, 0f32),
-                );
-
-                entities.build_entity()
-                    .with(Affiliation::Enemy(color.clone()), &mut affiliations)
-                    .with(collider, &mut collision_objects)
-                    .with(transform, &mut transforms)
-                    .with(object, &mut falling_objects)
-                    .with(spawner.sprite(), &mut sprites)
-                    .with(color.rgba(), &mut colors)
-                    .build();
+            let groups = collision_layers.groups("enemy");
+
+            for step in 0..timestep.steps() {
+                let spawn_count = spawner.advance(timestep.step_seconds()).min(spawner.remaining);
+                if spawn_count > 0 && !spawner.started {
+                    spawner.started = true;
+                    gameplay_events.single_write(GameplayEvent::WaveStarted { blocks: spawner.remaining });
+                }
+                for _ in 0..spawn_count {
+                    spawner.remaining -= 1;
+                    let mut rng = thread_rng();
+
+                    // randomize the falling object's speed and spin
+                    let object = FallingObject {
+                        fall_rate: rng.gen_range(60.0, 120.0),
+                        spin_rate: rng.gen_range(0.25, 1.5) * PI * rng.plus_or_minus(1.0),
+                        radius: SPAWNED_OBJECT_RADIUS,
+                    };
+
+                    // The FallingObjectSystem will move the new object for every step this frame,
+                    // so objects spawned in a later step start higher up to make up for the
+                    // steps they "missed".
+                    let missed_time = step as f32 * timestep.step_seconds();
+
+                    // pick a random starting position along the top of the screen
+                    let mut transform = Transform::default();
+                    transform.set_xyz(
+                        rng.gen_range(0.0, arena.width),
+                        arena.height + SPAWNED_OBJECT_RADIUS + object.fall_rate * missed_time,
+                        0.0,
+                    );
+
+                    let color = rng.gen::<ColorType>();
+
+                    let collider = Collider::new(
+                        ShapeHandle::new(Ball::new(SPAWNED_OBJECT_RADIUS)),
+                        groups.clone(),
+                        GeometricQueryType::Contacts(0f32, 0f32),
+                    );
+
+                    let (x, fall_rate) = (transform.translation().x, object.fall_rate);
+
+                    let block = entities.build_entity()
+                        .with(Affiliation::Enemy(color.clone()), &mut affiliations)
+                        .with(collider, &mut collision_objects)
+                        .with(transform, &mut transforms)
+                        .with(object, &mut falling_objects)
+                        .with(spawner.sprite(), &mut sprites)
+                        .with(color.rgba(), &mut colors)
+                        .build();
+
+                    // blocks always get an animation, so they can shatter when they're run into
+                    let animation = animations.get("block_falling")
+                        .unwrap_or_else(|| Arc::new(Animation::still(spawner.sprite())));
+                    sprite_animations.insert(block, SpriteAnimation::new(animation)).unwrap();
+
+                    gameplay_events.single_write(GameplayEvent::Spawned { block: block.into(), color, x, fall_rate });
+                }
             }
         }
     }
 }
-
-
-
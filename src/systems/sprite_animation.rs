@@ -0,0 +1,29 @@
+use amethyst::{
+    core::Time,
+    ecs::prelude::{Join, Read, System, WriteStorage},
+    renderer::SpriteRender,
+};
+
+use crate::animation::SpriteAnimation;
+
+/// System that advances every `SpriteAnimation`, and shows its current frame.
+pub struct SpriteAnimationSystem;
+
+impl<'s> System<'s> for SpriteAnimationSystem {
+    type SystemData = (
+        Read<'s, Time>,
+        WriteStorage<'s, SpriteAnimation>,
+        WriteStorage<'s, SpriteRender>,
+    );
+
+    fn run(&mut self, (time, mut animations, mut sprites): Self::SystemData) {
+        for (animation, sprite) in (&mut animations, &mut sprites).join() {
+            let animation: &mut SpriteAnimation = animation;
+            animation.advance(time.delta_seconds());
+
+            if let Some(frame) = animation.current_frame() {
+                *sprite = frame.clone();
+            }
+        }
+    }
+}
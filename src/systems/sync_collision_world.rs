@@ -1,33 +1,145 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
 use amethyst::{
     core::{
-        nalgebra::Isometry3,
+        nalgebra::{Isometry3, Vector3},
         transform::Transform,
     },
-    ecs::{
-        prelude::*,
-        ReaderId,
-    },
+    ecs::prelude::*,
     shrev::EventChannel,
 };
-use hibitset::DrainableBitSet;
+use log::trace;
+use ncollide3d::bounding_volume::BoundingVolume;
 use ncollide3d::events::ContactEvent;
+use ncollide3d::query::{self, Proximity};
 use ncollide3d::world::CollisionObjectHandle;
 use ncollide3d::world::CollisionWorld;
 
-use crate::falldown::{Collider, EntityCollisionWorld, EntityContactEvent};
-use crate::storage::DetailedComponentEvent;
-use crate::storage::RemovalBroadcaster;
+use crate::falldown::{Collider, CollisionEventKind, ContactPoint, EntityCollisionEvent, EntityCollisionWorld};
+use crate::storage::{DetailedComponentEvent, DetailedReader};
+
+#[cfg(test)]
+mod tests;
+
+/// Colliders that move further than this fraction of their bounding radius
+/// in a single frame are swept along their path, so they can't tunnel through
+/// other colliders between frames.
+const SWEEP_THRESHOLD: f32 = 0.5;
+
+const LOG_TARGET: &str = "falldown::collision";
+
+/// Where a collider was at the end of the previous frame, and where it is now.
+struct Motion {
+    previous: Isometry3<f32>,
+    current: Isometry3<f32>,
+}
+
+impl Motion {
+    fn displacement(&self) -> Vector3<f32> {
+        self.current.translation.vector - self.previous.translation.vector
+    }
+}
 
+/// System that copies the positions of colliders into the collision world, updates it,
+/// and reports the collisions between their entities as `EntityCollisionEvent`s.
+/// The colliders themselves are added to and removed from the world by a
+/// `SyncExternalWorld<Collider>` system, which has to run before this one.
 #[derive(Default)]
 pub struct SyncCollisionWorld {
-    collision_reader_id: Option<ReaderId<DetailedComponentEvent<Option<CollisionObjectHandle>>>>,
-    removed_colliders_vec: Vec<CollisionObjectHandle>,
-    added_colliders: BitSet,
+    reader: Option<DetailedReader<Option<CollisionObjectHandle>>>,
+    /// The entity each collision object belongs to. Objects removed from the world can still
+    /// show up in its events, so they're moved to `removed_entities` until the end of the frame.
+    entities: HashMap<CollisionObjectHandle, Entity>,
+    removed_entities: HashMap<CollisionObjectHandle, Entity>,
+    motions: HashMap<CollisionObjectHandle, Motion>,
+    swept_contacts: HashSet<(CollisionObjectHandle, CollisionObjectHandle)>,
+    started_contacts: HashSet<(CollisionObjectHandle, CollisionObjectHandle)>,
+    touching_contacts: HashSet<(CollisionObjectHandle, CollisionObjectHandle)>,
 }
 impl SyncCollisionWorld {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Find the pairs of colliders that touched at some point while moving from their
+    /// previous positions to their current ones, and store them in `swept_contacts`.
+    /// Only pairs where at least one of the colliders moved far enough to possibly skip
+    /// past something are swept.
+    fn sweep_fast_colliders(&mut self, collision_world: &EntityCollisionWorld) {
+        self.swept_contacts.clear();
+
+        // the volume each collider swept out this frame. These are compared with each other directly,
+        // because the world's broad phase still has last frame's volumes, and none for new colliders.
+        let mut swept: Vec<_> = self.motions.iter()
+            .map(|(handle, motion)| {
+                let object = collision_world.collision_object(*handle)
+                    .expect("Collider's handle doesn't exist in the world");
+                let shape = object.shape();
+                let aabb = shape.aabb(&motion.previous).merged(&shape.aabb(&motion.current));
+                let radius = shape.bounding_sphere(&motion.previous).radius();
+                let fast = motion.displacement().norm() > radius * SWEEP_THRESHOLD;
+                (*handle, object, motion, aabb, fast)
+            })
+            .collect();
+
+        // sort and sweep along the x axis: with the volumes sorted by their left edge,
+        // each one only overlaps the ones that start before it ends
+        swept.sort_by(|a, b| a.3.mins().x.partial_cmp(&b.3.mins().x).unwrap_or(Ordering::Equal));
+
+        for (i, (handle, object, motion, aabb, fast)) in swept.iter().enumerate() {
+            for (other_handle, other, other_motion, other_aabb, other_fast) in swept[i + 1..].iter() {
+                if other_aabb.mins().x > aabb.maxs().x {
+                    break;
+                }
+                if !(*fast || *other_fast) || !aabb.intersects(other_aabb) {
+                    continue;
+                }
+                if !object.collision_groups().can_interact_with_groups(other.collision_groups()) {
+                    continue;
+                }
+
+                // the displacements are per-frame, so a time of impact in [0, 1] means the
+                // two shapes touched during this frame. Shapes touching at 0 were already
+                // in contact at the end of the previous frame.
+                let toi = query::time_of_impact(
+                    &motion.previous, &motion.displacement(), &**object.shape(),
+                    &other_motion.previous, &other_motion.displacement(), &**other.shape(),
+                );
+                if let Some(toi) = toi {
+                    if toi > 0.0 && toi <= 1.0 {
+                        trace!(target: LOG_TARGET, "Swept contact between {} and {} at {}", handle.0, other_handle.0, toi);
+                        self.swept_contacts.insert(ordered_pair(*handle, *other_handle));
+                    }
+                }
+            }
+        }
+    }
+
+    fn collision_event(
+        &self,
+        (collider1, collider2): (CollisionObjectHandle, CollisionObjectHandle),
+        kind: CollisionEventKind,
+    ) -> Option<EntityCollisionEvent> {
+        // a removed object's handle can be reused by an object inserted in the same frame,
+        // but only the removed one can stop touching something
+        let ending = match kind {
+            CollisionEventKind::ContactStopped | CollisionEventKind::ProximityExited => true,
+            _ => false,
+        };
+        let entity = |handle: CollisionObjectHandle| if ending {
+            self.removed_entities.get(&handle).or_else(|| self.entities.get(&handle))
+        } else {
+            self.entities.get(&handle).or_else(|| self.removed_entities.get(&handle))
+        };
+        let entity1 = *entity(collider1)?;
+        let entity2 = *entity(collider2)?;
+        Some(EntityCollisionEvent { entity1, entity2, kind })
+    }
+}
+
+fn ordered_pair(a: CollisionObjectHandle, b: CollisionObjectHandle) -> (CollisionObjectHandle, CollisionObjectHandle) {
+    if a.0 <= b.0 { (a, b) } else { (b, a) }
 }
 
 impl<'s> System<'s> for SyncCollisionWorld {
@@ -36,7 +148,7 @@ impl<'s> System<'s> for SyncCollisionWorld {
         WriteExpect<'s, EntityCollisionWorld>,
         WriteStorage<'s, Collider>,
         ReadStorage<'s, Transform>,
-        Write<'s, EventChannel<EntityContactEvent>>
+        Write<'s, EventChannel<EntityCollisionEvent>>
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -45,77 +157,145 @@ impl<'s> System<'s> for SyncCollisionWorld {
             mut collision_world,
             mut colliders,
             transforms,
-            mut contact_events,
+            mut collision_events,
         ) = data;
 
-        // collect the Added and Removed colliders from the event channel
-        self.removed_colliders_vec.clear();
-        self.added_colliders.clear();
-        let events = colliders.detailed_channel().read(self.collision_reader_id.as_mut().unwrap());
-        for event in events {
+        // keep track of which entity each object belongs to, including the ones that were just removed
+        self.removed_entities.clear();
+        let reader = self.reader.as_mut().expect("SyncCollisionWorld::setup was never called");
+        for event in reader.read_alive(&mut colliders, &entities) {
             match event {
-                DetailedComponentEvent::Modified(_) => {},
-                DetailedComponentEvent::Inserted(id) => {
-                    self.added_colliders.add(*id);
-                },
-                DetailedComponentEvent::Removed(_, Some(collider_handle)) => {
-                    self.removed_colliders_vec.push(*collider_handle);
+                DetailedComponentEvent::Inserted(_) | DetailedComponentEvent::Modified(_) => {},
+                DetailedComponentEvent::Removed(entity, handle) => {
+                    // colliders removed before they were ever synced don't have a handle yet
+                    if let Some(handle) = handle {
+                        self.entities.remove(handle);
+                        self.removed_entities.insert(*handle, *entity);
+                    }
                 },
-                DetailedComponentEvent::Removed(_, None) => {
-                    println!("Removed a collider that didn't have a handle set");
+                DetailedComponentEvent::Cleared(components) => {
+                    for (entity, handle) in components.iter() {
+                        if let Some(handle) = handle {
+                            self.entities.remove(handle);
+                            self.removed_entities.insert(*handle, *entity);
+                        }
+                    }
                 },
             }
         }
 
-        // insert any new colliders into the world
-        for id in self.added_colliders.drain() {
-            let entity = entities.entity(id);
-            let collider = colliders.get_mut(entity)
-                .expect("Failed to get a newly-added collider from storage");
-
-            let handle = collision_world
-                .add(
-                    Isometry3::identity(),
-                    collider.shape().clone(),
-                    collider.groups().clone(),
-                    collider.query_type().clone(),
-                    entity
-                )
-                .handle();
-            collider.handle = Some(handle);
-            // println!("Added {:?} to the world", handle);
+        // move any new colliders (including ones that replaced another collider) to where
+        // their entity is, so they don't look like they just moved there from the origin
+        let mut inserted = 0;
+        for (entity, collider, transform) in (&*entities, &mut colliders, transforms.maybe()).join() {
+            let handle = match collider.handle {
+                Some(handle) if !collider.positioned => handle,
+                _ => continue,
+            };
+            self.entities.insert(handle, entity);
+            if let Some(transform) = transform {
+                collision_world.set_position(handle, *transform.isometry());
+            }
+            collider.positioned = true;
+            inserted += 1;
+        }
+        if inserted > 0 || !self.removed_entities.is_empty() {
+            trace!(target: LOG_TARGET, "Added {} and removed {} collision objects", inserted, self.removed_entities.len());
         }
 
-        // remove any deleted colliders from the world
-        if !self.removed_colliders_vec.is_empty() {
-            // println!("Removing {:?}", self.removed_colliders_vec);
-            collision_world.remove(&self.removed_colliders_vec);
+        // find out how far each collider has moved since the last frame
+        self.motions.clear();
+        for (collider, transform) in (&colliders, &transforms).join() {
+            // colliders on entities that were deleted before they were synced never get a handle
+            let handle = match collider.handle {
+                Some(handle) => handle,
+                None => continue,
+            };
+            let previous = *collision_world.collision_object(handle)
+                .expect("Collider's handle doesn't exist in the world")
+                .position();
+            let current = *transform.isometry();
+            self.motions.insert(handle, Motion { previous, current });
         }
 
+        // sweep any fast-moving colliders along their path to find the contacts
+        // that would have happened between the previous frame and this one
+        self.sweep_fast_colliders(&collision_world);
+
         // copy the transforms from all collider entities into the collision world
-        for (collider, transform) in (&colliders, &transforms).join() {
-            collision_world.set_position(collider.handle.unwrap(), *transform.isometry());
+        for (handle, motion) in self.motions.iter() {
+            collision_world.set_position(*handle, motion.current);
         }
 
         // update the collision world
         collision_world.update();
 
         // process collision/contact events
-        // TODO: do something interesting here instead of just println!
+        self.started_contacts.clear();
         for event in collision_world.contact_events() {
-            if let &ContactEvent::Started(collider1, collider2) = event {
-                let entity1 = collision_world.collision_object(collider1)
-                    .expect("ContactEvent involving an object that doesn't exist in the world")
-                    .data();
-                let entity2 = collision_world.collision_object(collider2)
-                    .expect("ContactEvent involving an object that doesn't exist in the world")
-                    .data();
-                contact_events.single_write((*entity1, *entity2, *event));
-//                println!("Collision started between {:?} and {:?}", collider1, collider2);
+            let (collider1, collider2, started) = match *event {
+                ContactEvent::Started(collider1, collider2) => (collider1, collider2, true),
+                ContactEvent::Stopped(collider1, collider2) => (collider1, collider2, false),
+            };
+            let pair = ordered_pair(collider1, collider2);
+            if started {
+                // the contact points are filled in below, along with the persisting contacts
+                self.started_contacts.insert(pair);
+            } else {
+                collision_events.iter_write(self.collision_event(pair, CollisionEventKind::ContactStopped));
+            }
+        }
+
+        // emit Started or Persisting events (with contact points) for every pair that is touching
+        self.touching_contacts.clear();
+        for (object1, object2, _, manifold) in collision_world.contact_pairs(true) {
+            let pair = ordered_pair(object1.handle(), object2.handle());
+            let flip_normals = pair.0 != object1.handle();
+            let contacts = manifold.contacts()
+                .map(|tracked| {
+                    let contact = &tracked.contact;
+                    ContactPoint {
+                        point: if flip_normals { contact.world2 } else { contact.world1 },
+                        normal: if flip_normals { -contact.normal.into_inner() } else { contact.normal.into_inner() },
+                        depth: contact.depth,
+                    }
+                })
+                .collect();
+
+            self.touching_contacts.insert(pair);
+            let kind = if self.started_contacts.contains(&pair) {
+                CollisionEventKind::ContactStarted(contacts)
+            } else {
+                CollisionEventKind::ContactPersisting(contacts)
+            };
+            collision_events.iter_write(self.collision_event(pair, kind));
+        }
+
+        // Started contacts that weren't in the contact pairs (e.g. ones that were
+        // within the prediction margin, but not touching yet) still get reported
+        for &pair in self.started_contacts.difference(&self.touching_contacts) {
+            collision_events.iter_write(self.collision_event(pair, CollisionEventKind::ContactStarted(Vec::new())));
+        }
+
+        for event in collision_world.proximity_events() {
+            let was_intersecting = event.prev_status == Proximity::Intersecting;
+            let is_intersecting = event.new_status == Proximity::Intersecting;
+            let pair = (event.collider1, event.collider2);
+            if is_intersecting && !was_intersecting {
+                collision_events.iter_write(self.collision_event(pair, CollisionEventKind::ProximityEntered));
+            } else if was_intersecting && !is_intersecting {
+                collision_events.iter_write(self.collision_event(pair, CollisionEventKind::ProximityExited));
+            }
+        }
+
+        // Swept contacts that the world didn't pick up happened somewhere between the
+        // two frames, and were already over by the end of this frame. They never show up
+        // in the collision world, so report them as a contact that started and stopped.
+        for &pair in self.swept_contacts.iter() {
+            if !self.started_contacts.contains(&pair) && !self.touching_contacts.contains(&pair) {
+                collision_events.iter_write(self.collision_event(pair, CollisionEventKind::ContactStarted(Vec::new())));
+                collision_events.iter_write(self.collision_event(pair, CollisionEventKind::ContactStopped));
             }
-//            if let &ContactEvent::Stopped(collider1, collider2) = event {
-//                println!("Collision ended between {:?} and {:?}", collider1, collider2);
-//            }
         }
     }
 
@@ -125,7 +305,7 @@ impl<'s> System<'s> for SyncCollisionWorld {
         res.entry::<EntityCollisionWorld>()
             .or_insert_with(|| CollisionWorld::new(10.0));
 
-        let mut collision_storage: WriteStorage<Collider> = SystemData::fetch(res);
-        self.collision_reader_id = Some(collision_storage.register_detailed_reader());
+        let mut colliders: WriteStorage<Collider> = SystemData::fetch(res);
+        self.reader = Some(DetailedReader::new(&mut colliders));
     }
 }
\ No newline at end of file
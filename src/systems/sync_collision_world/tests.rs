@@ -0,0 +1,97 @@
+use amethyst::{core::transform::Transform, ecs::prelude::*};
+use ncollide3d::shape::{Ball, ShapeHandle};
+use ncollide3d::world::{CollisionGroups, GeometricQueryType};
+
+use super::*;
+use crate::storage::ModifyTracked;
+use crate::systems::SyncExternalWorld;
+
+fn setup<'a, 'b>() -> (World, Dispatcher<'a, 'b>) {
+    let mut world = World::new();
+    let mut dispatcher = DispatcherBuilder::new()
+        .with(SyncExternalWorld::<Collider>::new(), "sync_colliders", &[])
+        .with(SyncCollisionWorld::new(), "sync_collision", &["sync_colliders"])
+        .build();
+    dispatcher.setup(&mut world.res);
+    (world, dispatcher)
+}
+
+fn ball(radius: f32) -> Collider {
+    Collider::new(
+        ShapeHandle::new(Ball::new(radius)),
+        CollisionGroups::new(),
+        GeometricQueryType::Contacts(0.0, 0.0),
+    )
+}
+
+fn world_radius(world: &World, entity: Entity) -> f32 {
+    let handle = world.read_storage::<Collider>().get(entity).unwrap().handle
+        .expect("collider was never added to the collision world");
+    let collision_world = world.read_resource::<EntityCollisionWorld>();
+    let object = collision_world.collision_object(handle).unwrap();
+    object.shape().as_shape::<Ball<f32>>().expect("expected a ball").radius()
+}
+
+#[test]
+fn changed_shape_reaches_the_collision_world() {
+    let (mut world, mut dispatcher) = setup();
+    let e = world.create_entity().with(ball(1.0)).with(Transform::default()).build();
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world_radius(&world, e), 1.0);
+
+    world.write_storage::<Collider>().modify(e).unwrap().set_shape(ShapeHandle::new(Ball::new(2.0)));
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world_radius(&world, e), 2.0);
+}
+
+#[test]
+fn unreported_changes_are_not_copied() {
+    let (mut world, mut dispatcher) = setup();
+    let e = world.create_entity().with(ball(1.0)).with(Transform::default()).build();
+    dispatcher.dispatch(&world.res);
+
+    world.write_storage::<Collider>().get_mut(e).unwrap().set_shape(ShapeHandle::new(Ball::new(2.0)));
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world_radius(&world, e), 1.0);
+}
+
+#[test]
+fn replaced_collider_replaces_its_collision_object() {
+    let (mut world, mut dispatcher) = setup();
+    let e = world.create_entity().with(ball(1.0)).with(Transform::default()).build();
+    dispatcher.dispatch(&world.res);
+
+    world.write_storage::<Collider>().insert(e, ball(3.0)).unwrap();
+    dispatcher.dispatch(&world.res);
+
+    assert_eq!(world_radius(&world, e), 3.0);
+    assert_eq!(world.read_resource::<EntityCollisionWorld>().collision_objects().count(), 1);
+}
+
+#[test]
+fn colliders_of_deleted_entities_are_never_synced() {
+    let (mut world, mut dispatcher) = setup();
+    let e = world.create_entity().with(ball(1.0)).with(Transform::default()).build();
+    world.entities().delete(e).unwrap();
+
+    // the collider stays in its storage until the world is maintained
+    dispatcher.dispatch(&world.res);
+    assert!((&world.read_storage::<Collider>()).join().all(|collider| collider.handle.is_none()));
+
+    world.maintain();
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world.read_resource::<EntityCollisionWorld>().collision_objects().count(), 0);
+}
+
+#[test]
+fn cleared_colliders_leave_the_collision_world() {
+    let (mut world, mut dispatcher) = setup();
+    world.create_entity().with(ball(1.0)).with(Transform::default()).build();
+    world.create_entity().with(ball(2.0)).with(Transform::default()).build();
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world.read_resource::<EntityCollisionWorld>().collision_objects().count(), 2);
+
+    world.write_storage::<Collider>().clear();
+    dispatcher.dispatch(&world.res);
+    assert_eq!(world.read_resource::<EntityCollisionWorld>().collision_objects().count(), 0);
+}
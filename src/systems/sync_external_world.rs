@@ -0,0 +1,51 @@
+use amethyst::ecs::prelude::*;
+
+use crate::external_sync::{ComponentSync, ExternalSync};
+use crate::storage::{MarkModified, RemovalTracked, ToEvent};
+
+#[cfg(test)]
+mod tests;
+
+/// System that keeps the `C::World` resource in sync with the `C` components in the ECS,
+/// inserting, updating and removing their mirrors as the components are added, modified and removed.
+/// The external world resource must have been added before this system runs.
+pub struct SyncExternalWorld<C>
+where
+    C: ExternalSync + ToEvent<Option<<C as ExternalSync>::Handle>>,
+    C::Storage: RemovalTracked<Option<<C as ExternalSync>::Handle>> + MarkModified,
+{
+    sync: ComponentSync<C>,
+}
+
+impl<C> SyncExternalWorld<C>
+where
+    C: ExternalSync + ToEvent<Option<<C as ExternalSync>::Handle>>,
+    C::Storage: RemovalTracked<Option<<C as ExternalSync>::Handle>> + MarkModified,
+{
+    pub fn new() -> Self {
+        SyncExternalWorld {
+            sync: ComponentSync::default(),
+        }
+    }
+}
+
+impl<'s, C> System<'s> for SyncExternalWorld<C>
+where
+    C: ExternalSync + ToEvent<Option<<C as ExternalSync>::Handle>>,
+    C::Storage: RemovalTracked<Option<<C as ExternalSync>::Handle>> + MarkModified,
+{
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, C>,
+        WriteExpect<'s, C::World>,
+    );
+
+    fn run(&mut self, (entities, mut storage, mut world): Self::SystemData) {
+        self.sync.sync(&entities, &mut storage, &mut world);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.sync.setup(res);
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use amethyst::ecs::prelude::*;
+
+use super::*;
+use crate::storage::{DetectChanges, ModifyTracked, RemovalFlaggedStorage};
+
+/// A component mirrored into a plain map, standing in for something like an audio mixer.
+struct Volume {
+    level: u32,
+    handle: Option<usize>,
+}
+
+impl Volume {
+    fn new(level: u32) -> Volume {
+        Volume { level, handle: None }
+    }
+}
+
+#[derive(Default)]
+struct Mixer {
+    channels: HashMap<usize, (Entity, u32)>,
+    next_handle: usize,
+}
+
+impl ToEvent<Option<usize>> for Volume {
+    fn to_event(&self) -> Option<usize> {
+        self.handle
+    }
+}
+impl Component for Volume {
+    type Storage = RemovalFlaggedStorage<Self, Option<usize>>;
+}
+impl DetectChanges for Volume {
+    type Snapshot = u32;
+
+    fn snapshot(&self) -> u32 { self.level }
+
+    fn changed_since(&self, level: &u32) -> bool { self.level != *level }
+}
+impl ExternalSync for Volume {
+    type World = Mixer;
+    type Handle = usize;
+
+    fn handle(&self) -> Option<usize> { self.handle }
+
+    fn set_handle(&mut self, handle: usize) { self.handle = Some(handle); }
+
+    fn insert_into(&self, entity: Entity, mixer: &mut Mixer) -> usize {
+        let handle = mixer.next_handle;
+        mixer.next_handle += 1;
+        mixer.channels.insert(handle, (entity, self.level));
+        handle
+    }
+
+    fn update_in(&self, handle: &usize, mixer: &mut Mixer) {
+        mixer.channels.get_mut(handle).unwrap().1 = self.level;
+    }
+
+    fn remove_from(handles: &[usize], mixer: &mut Mixer) {
+        for handle in handles {
+            mixer.channels.remove(handle).expect("removed a channel twice");
+        }
+    }
+}
+
+fn setup<'a, 'b>() -> (World, Dispatcher<'a, 'b>) {
+    let mut world = World::new();
+    world.add_resource(Mixer::default());
+    let mut dispatcher = DispatcherBuilder::new()
+        .with(SyncExternalWorld::<Volume>::new(), "sync_volumes", &[])
+        .build();
+    dispatcher.setup(&mut world.res);
+    (world, dispatcher)
+}
+
+/// The mixer's channels, as (entity, level) pairs sorted by entity.
+fn channels(world: &World) -> Vec<(Entity, u32)> {
+    let mut channels: Vec<_> = world.read_resource::<Mixer>().channels.values().cloned().collect();
+    channels.sort_by_key(|(entity, _)| entity.id());
+    channels
+}
+
+#[test]
+fn inserted_components_are_mirrored() {
+    let (mut world, mut dispatcher) = setup();
+    let e1 = world.create_entity().with(Volume::new(1)).build();
+    let e2 = world.create_entity().with(Volume::new(2)).build();
+    dispatcher.dispatch(&world.res);
+
+    assert_eq!(channels(&world), vec![(e1, 1), (e2, 2)]);
+    assert!(world.read_storage::<Volume>().get(e1).unwrap().handle.is_some());
+}
+
+#[test]
+fn modified_components_are_updated() {
+    let (mut world, mut dispatcher) = setup();
+    let e = world.create_entity().with(Volume::new(1)).build();
+    dispatcher.dispatch(&world.res);
+
+    world.write_storage::<Volume>().modify(e).unwrap().level = 5;
+    dispatcher.dispatch(&world.res);
+
+    assert_eq!(channels(&world), vec![(e, 5)]);
+}
+
+#[test]
+fn removed_components_are_removed_from_the_mirror() {
+    let (mut world, mut dispatcher) = setup();
+    let e1 = world.create_entity().with(Volume::new(1)).build();
+    let e2 = world.create_entity().with(Volume::new(2)).build();
+    dispatcher.dispatch(&world.res);
+
+    world.write_storage::<Volume>().remove(e1);
+    world.delete_entity(e2).unwrap();
+    world.maintain();
+    dispatcher.dispatch(&world.res);
+
+    assert_eq!(channels(&world), vec![]);
+}
+
+#[test]
+fn replaced_components_replace_their_mirror() {
+    let (mut world, mut dispatcher) = setup();
+    let e = world.create_entity().with(Volume::new(1)).build();
+    dispatcher.dispatch(&world.res);
+
+    let old = world.write_storage::<Volume>().insert(e, Volume::new(7)).unwrap();
+    assert!(old.is_some(), "expected the old component to be replaced");
+    dispatcher.dispatch(&world.res);
+
+    assert_eq!(channels(&world), vec![(e, 7)]);
+}
+
+#[test]
+fn cleared_storage_clears_the_mirror() {
+    let (mut world, mut dispatcher) = setup();
+    world.create_entity().with(Volume::new(1)).build();
+    world.create_entity().with(Volume::new(2)).build();
+    dispatcher.dispatch(&world.res);
+
+    world.write_storage::<Volume>().clear();
+    dispatcher.dispatch(&world.res);
+
+    assert_eq!(channels(&world), vec![]);
+}
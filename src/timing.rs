@@ -0,0 +1,50 @@
+/// Resource that turns the variable frame time into a whole number of fixed-size
+/// simulation steps. Gameplay systems should advance their state once per step,
+/// using `step_seconds()` as their delta, so that the game behaves the same
+/// regardless of the frame rate.
+pub struct FixedTimestep {
+    step_seconds: f32,
+    max_steps_per_frame: u32,
+    accumulator: f32,
+    steps: u32,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        FixedTimestep::new(1.0 / 120.0, 12)
+    }
+}
+
+impl FixedTimestep {
+    pub fn new(step_seconds: f32, max_steps_per_frame: u32) -> FixedTimestep {
+        FixedTimestep {
+            step_seconds,
+            max_steps_per_frame,
+            accumulator: 0.0,
+            steps: 0,
+        }
+    }
+
+    /// Add a frame's worth of time to the accumulator, and decide how many
+    /// fixed steps should run this frame. If the frame took so long that more
+    /// than `max_steps_per_frame` steps would be needed, the excess time is
+    /// dropped so that a slow frame can't snowball into even slower frames.
+    pub fn advance(&mut self, frame_seconds: f32) {
+        self.accumulator += frame_seconds;
+        let mut steps = 0;
+        while self.accumulator >= self.step_seconds && steps < self.max_steps_per_frame {
+            self.accumulator -= self.step_seconds;
+            steps += 1;
+        }
+        if steps == self.max_steps_per_frame {
+            self.accumulator = self.accumulator.min(self.step_seconds);
+        }
+        self.steps = steps;
+    }
+
+    /// The duration of a single simulation step, in seconds.
+    pub fn step_seconds(&self) -> f32 { self.step_seconds }
+
+    /// The number of simulation steps that should be run this frame.
+    pub fn steps(&self) -> u32 { self.steps }
+}
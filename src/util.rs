@@ -17,4 +17,13 @@ impl <R> RngExtras for R
             -value
         }
     }
-}
\ No newline at end of file
+}
+
+/// Move `current` towards `target` so that, however the time is split up, the distance
+/// between them halves every `half_life` seconds.
+pub fn ease_towards(current: f32, target: f32, half_life: f32, delta_seconds: f32) -> f32 {
+    if half_life <= 0.0 {
+        return target;
+    }
+    target + (current - target) * 0.5f32.powf(delta_seconds / half_life)
+}